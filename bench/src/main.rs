@@ -0,0 +1,120 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmark of the light client guest cycles across numbers of interval headers, validator set
+//! sizes, validator churn and validator key types, using synthetic inputs. Outputs a CSV row for
+//! each combination.
+
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use anyhow::Context;
+use blobstream0_core::execute_block;
+use clap::Parser;
+use synthetic::SyntheticParams;
+
+mod synthetic;
+
+const CSV_HEADER: &str = "interval_headers,validators,churned_validators,secp256k1_validators,\
+                          input_bytes,segments,total_cycles,user_cycles,decode,bitmap,merkle_root,\
+                          light_client_verify";
+
+#[derive(Parser, Debug)]
+#[command(name = "blobstream0-bench")]
+struct Args {
+    /// Numbers of headers between the trusted and untrusted blocks
+    #[clap(long, value_delimiter = ',', default_value = "0,16,64,256")]
+    headers: Vec<u64>,
+
+    /// Numbers of validators in the validator sets
+    #[clap(long, value_delimiter = ',', default_value = "4,32,100")]
+    validators: Vec<usize>,
+
+    /// Percentages of validators replaced between the trusted and untrusted blocks. Must be less
+    /// than 33 for the transition to be verified.
+    #[clap(long, value_delimiter = ',', default_value = "0")]
+    churn_percent: Vec<usize>,
+
+    /// Percentages of validators with secp256k1 keys, the rest having ed25519 keys
+    #[clap(long, value_delimiter = ',', default_value = "0")]
+    secp256k1_percent: Vec<usize>,
+
+    /// Seed for the generated inputs
+    #[clap(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Output file for the CSV, defaults to stdout
+    #[clap(long, short)]
+    out: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let mut out: Box<dyn Write> = match &args.out {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    writeln!(out, "{}", CSV_HEADER)?;
+
+    for &interval_headers in &args.headers {
+        for &validators in &args.validators {
+            for &churn_percent in &args.churn_percent {
+                for &secp256k1_percent in &args.secp256k1_percent {
+                    let params = SyntheticParams {
+                        interval_headers,
+                        validators,
+                        churned_validators: validators * churn_percent / 100,
+                        secp256k1_validators: validators * secp256k1_percent / 100,
+                        seed: args.seed,
+                    };
+                    let input = match synthetic::generate(params) {
+                        Ok(input) => input,
+                        Err(e) => {
+                            eprintln!("skipping {:?}: {}", params, e);
+                            continue;
+                        }
+                    };
+
+                    let report = execute_block(input).await?;
+                    let phases = report.phase_cycles.context(
+                        "guest did not report phase cycles, run with --features cycle-report",
+                    )?;
+                    writeln!(
+                        out,
+                        "{},{},{},{},{},{},{},{},{},{},{},{}",
+                        params.interval_headers,
+                        params.validators,
+                        params.churned_validators,
+                        params.secp256k1_validators,
+                        report.input_bytes,
+                        report.segments,
+                        report.total_cycles,
+                        report.user_cycles,
+                        phases.decode,
+                        phases.bitmap,
+                        phases.merkle_root,
+                        phases.light_client_verify
+                    )?;
+                    out.flush()?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,139 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of synthetic light client inputs, with validator sets of ed25519 and secp256k1 keys
+//! that sign the commits of the trusted and untrusted blocks.
+
+use blobstream0_primitives::{
+    proto::{TrustedLightBlock, UntrustedLightBlock},
+    test_utils::{block_id, commit, header, validator_set, SigningKey, Validator},
+    LightBlockProveData, ProofConfig,
+};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use tendermint::{block::signed_header::SignedHeader, Hash};
+
+/// Height of the trusted block.
+const TRUSTED_HEIGHT: u64 = 1_000;
+
+/// Voting power of each validator.
+const VALIDATOR_POWER: u32 = 10;
+
+/// Parameters of a synthetic light client input.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SyntheticParams {
+    /// Number of headers between the trusted and untrusted blocks.
+    pub interval_headers: u64,
+    /// Number of validators in the trusted and untrusted validator sets.
+    pub validators: usize,
+    /// Number of trusted validators replaced with new validators in the untrusted validator set.
+    /// Must be less than a third of the validators for the transition to be verified.
+    pub churned_validators: usize,
+    /// Number of validators with secp256k1 keys, the rest having ed25519 keys. Validators that
+    /// replace churned validators have the same key type as the validator they replace.
+    pub secp256k1_validators: usize,
+    /// Seed for the generated keys and hashes.
+    pub seed: u64,
+}
+
+/// Validator with a new random key.
+fn random_validator(rng: &mut StdRng, secp256k1: bool) -> Validator {
+    let key = if secp256k1 {
+        SigningKey::Secp256k1(k256::ecdsa::SigningKey::random(rng))
+    } else {
+        SigningKey::Ed25519(ed25519_consensus::SigningKey::new(rng))
+    };
+    Validator::new(key, VALIDATOR_POWER)
+}
+
+/// Generates the inputs to prove a transition from a trusted block to an untrusted block, with
+/// every validator signing both blocks.
+pub(crate) fn generate(params: SyntheticParams) -> anyhow::Result<LightBlockProveData> {
+    anyhow::ensure!(params.validators > 0, "at least one validator is required");
+    anyhow::ensure!(
+        params.churned_validators * 3 < params.validators,
+        "churned validators must be less than a third of the validators"
+    );
+    anyhow::ensure!(
+        params.churned_validators == 0 || params.interval_headers > 0,
+        "validators can only change with interval headers, as adjacent blocks must have the \
+         next validators of the trusted block"
+    );
+    anyhow::ensure!(
+        params.secp256k1_validators <= params.validators,
+        "secp256k1 validators cannot be more than the validators"
+    );
+
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let trusted_validators: Vec<_> = (0..params.validators)
+        .map(|i| random_validator(&mut rng, i < params.secp256k1_validators))
+        .collect();
+    let new_validators: Vec<_> = (0..params.churned_validators)
+        .map(|i| random_validator(&mut rng, i < params.secp256k1_validators))
+        .collect();
+
+    let trusted_set = validator_set(&trusted_validators);
+    let untrusted_set = validator_set(
+        trusted_validators[params.churned_validators..]
+            .iter()
+            .chain(&new_validators),
+    );
+
+    // The block before the trusted block is not included, so can be any hash.
+    let trusted_last_block_id = block_id(Hash::Sha256(random_bytes(&mut rng)));
+    let trusted_header = header(
+        TRUSTED_HEIGHT,
+        trusted_last_block_id,
+        &trusted_set,
+        &trusted_set,
+    );
+
+    let untrusted_height = TRUSTED_HEIGHT + params.interval_headers + 1;
+    let mut interval_headers = Vec::with_capacity(params.interval_headers as usize);
+    let mut previous_hash = trusted_header.hash();
+    for height in TRUSTED_HEIGHT + 1..untrusted_height {
+        let header = header(height, block_id(previous_hash), &trusted_set, &trusted_set);
+        previous_hash = header.hash();
+        interval_headers.push(header);
+    }
+
+    let untrusted_header = header(
+        untrusted_height,
+        block_id(previous_hash),
+        &untrusted_set,
+        &untrusted_set,
+    );
+
+    let signers = || trusted_validators.iter().chain(&new_validators);
+    let trusted_commit = commit(&trusted_header, &trusted_set, signers(), None, None);
+    let untrusted_commit = commit(&untrusted_header, &untrusted_set, signers(), None, None);
+
+    Ok(LightBlockProveData {
+        trusted_block: TrustedLightBlock {
+            signed_header: SignedHeader::new(trusted_header, trusted_commit)?,
+            next_validators: trusted_set,
+        },
+        interval_headers,
+        untrusted_block: UntrustedLightBlock {
+            signed_header: SignedHeader::new(untrusted_header, untrusted_commit)?,
+            validators: untrusted_set,
+        },
+        config: ProofConfig::default(),
+    })
+}
+
+fn random_bytes(rng: &mut StdRng) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    rng.fill_bytes(&mut bytes);
+    bytes
+}
@@ -0,0 +1,409 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Owner only transactions to manage the deployed contract, which show the changes to the
+//! contract state and ask for confirmation before sending, or are exported for a Safe owner.
+
+use std::io::{self, BufRead, Write};
+
+use alloy::{
+    network::TransactionBuilder,
+    primitives::{Address, Bytes, B256, U256},
+    providers::{Provider, ProviderBuilder},
+    rpc::types::TransactionRequest,
+};
+use alloy_sol_types::SolCall;
+use blobstream0_core::{light_client_image_id, ContractError};
+use blobstream0_primitives::{
+    chain_id_hash,
+    IBlobstream::{self, IBlobstreamInstance},
+};
+use clap::{Args, Subcommand};
+use tendermint::chain;
+
+use crate::{
+    safe::{self, SafeExportArgs, SafeTransaction},
+    signer::{ensure_chain_id, SignerArgs},
+};
+
+/// Arguments shared by all admin commands.
+#[derive(Args, Debug)]
+pub(crate) struct AdminOptions {
+    /// The Ethereum RPC URL
+    #[clap(long, env)]
+    eth_rpc: String,
+
+    /// Expected chain ID of the Ethereum RPC, checked before sending any transactions
+    #[clap(long, env)]
+    eth_chain_id: Option<u64>,
+
+    /// The deployed contract on Ethereum to manage
+    #[clap(long, env)]
+    eth_address: Address,
+
+    #[clap(flatten)]
+    signer: SignerArgs,
+
+    #[clap(flatten)]
+    safe: SafeExportArgs,
+
+    /// Send the transaction without asking for confirmation
+    #[clap(long, short)]
+    yes: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum AdminCommand {
+    /// Set the trusted block hash and height the next range is proven from.
+    SetTrustedState {
+        /// Trusted block hash
+        #[clap(long)]
+        trusted_hash: B256,
+
+        /// Trusted block height
+        #[clap(long)]
+        trusted_height: u64,
+
+        #[clap(flatten)]
+        options: AdminOptions,
+    },
+    /// Set the image ID of the light client guest proofs are verified against.
+    SetImageId {
+        /// Image ID to set. Defaults to the image ID of the light client guest built with this
+        /// CLI.
+        #[clap(long)]
+        image_id: Option<B256>,
+
+        #[clap(flatten)]
+        options: AdminOptions,
+    },
+    /// Set the Tendermint chain ID that proofs must be verified against.
+    SetChainId {
+        /// Tendermint chain ID, such as `celestia` or `mocha-4`
+        #[clap(long)]
+        tm_chain_id: chain::Id,
+
+        #[clap(flatten)]
+        options: AdminOptions,
+    },
+    /// Set the RISC Zero verifier contract.
+    SetVerifier {
+        /// Address of the verifier contract
+        #[clap(long)]
+        verifier_address: Address,
+
+        #[clap(flatten)]
+        options: AdminOptions,
+    },
+    /// Start a transfer of ownership, which the new owner completes with `accept-ownership`.
+    TransferOwnership {
+        /// Address of the new owner
+        #[clap(long)]
+        new_owner: Address,
+
+        #[clap(flatten)]
+        options: AdminOptions,
+    },
+    /// Accept a pending transfer of ownership to the signer.
+    AcceptOwnership {
+        #[clap(flatten)]
+        options: AdminOptions,
+    },
+}
+
+/// Contract state that admin commands can change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AdminState {
+    latest_height: u64,
+    latest_block_hash: B256,
+    image_id: B256,
+    /// Not available before the contract is upgraded to a version with the chain ID hash.
+    chain_id_hash: Option<B256>,
+    verifier: Address,
+    owner: Address,
+    pending_owner: Address,
+}
+
+impl AdminState {
+    async fn read<P: Provider>(contract: &IBlobstreamInstance<P>) -> anyhow::Result<Self> {
+        let (height, hash, image_id, chain_id, verifier, owner, pending_owner) = tokio::try_join!(
+            async { contract.latestHeight().call().await },
+            async { contract.latestBlockHash().call().await },
+            async { contract.imageId().call().await },
+            async {
+                match contract.chainIdHash().call().await {
+                    Ok(chain_id) => Ok(Some(chain_id)),
+                    Err(e) if is_revert(&e) => Ok(None),
+                    Err(e) => Err(e),
+                }
+            },
+            async { contract.verifier().call().await },
+            async { contract.owner().call().await },
+            async { contract.pendingOwner().call().await },
+        )?;
+        Ok(Self {
+            latest_height: height,
+            latest_block_hash: hash,
+            image_id,
+            chain_id_hash: chain_id,
+            verifier,
+            owner,
+            pending_owner,
+        })
+    }
+
+    fn fields(&self) -> [(&'static str, String); 7] {
+        [
+            ("latest height", self.latest_height.to_string()),
+            ("latest block hash", self.latest_block_hash.to_string()),
+            ("image ID", self.image_id.to_string()),
+            (
+                "chain ID hash",
+                self.chain_id_hash
+                    .map_or_else(|| "unavailable".to_string(), |hash| hash.to_string()),
+            ),
+            ("verifier", self.verifier.to_string()),
+            ("owner", self.owner.to_string()),
+            ("pending owner", self.pending_owner.to_string()),
+        ]
+    }
+
+    /// Descriptions of the fields that differ from `other`.
+    fn diff(&self, other: &Self) -> Vec<String> {
+        self.fields()
+            .into_iter()
+            .zip(other.fields())
+            .filter(|((_, before), (_, after))| before != after)
+            .map(|((name, before), (_, after))| format!("{}: {} -> {}", name, before, after))
+            .collect()
+    }
+
+    /// Prints the fields that differ from `other`.
+    fn print_diff(&self, other: &Self) {
+        let changes = self.diff(other);
+        if changes.is_empty() {
+            println!("  no changes");
+        }
+        for change in changes {
+            println!("  {}", change);
+        }
+    }
+}
+
+impl AdminCommand {
+    fn options(&self) -> &AdminOptions {
+        match self {
+            AdminCommand::SetTrustedState { options, .. }
+            | AdminCommand::SetImageId { options, .. }
+            | AdminCommand::SetChainId { options, .. }
+            | AdminCommand::SetVerifier { options, .. }
+            | AdminCommand::TransferOwnership { options, .. }
+            | AdminCommand::AcceptOwnership { options } => options,
+        }
+    }
+
+    pub(crate) async fn run(self) -> anyhow::Result<()> {
+        let options = self.options();
+        if let Some((path, safe_address)) = options.safe.target() {
+            let provider = ProviderBuilder::new().connect_http(options.eth_rpc.parse()?);
+            ensure_chain_id(&provider, options.eth_chain_id).await?;
+            let contract = IBlobstream::new(options.eth_address, provider);
+            let (before, expected) = self.plan(&contract, safe_address).await?;
+
+            println!("contract: {}", options.eth_address);
+            println!("safe:     {}", safe_address);
+            println!("changes:");
+            before.print_diff(&expected);
+            safe::write_batch(
+                path,
+                contract.provider().get_chain_id().await?,
+                safe_address,
+                "Blobstream0 admin",
+                &before.diff(&expected).join(", "),
+                &[SafeTransaction {
+                    to: options.eth_address,
+                    value: U256::ZERO,
+                    data: self.calldata(&expected),
+                }],
+            )?;
+            return Ok(());
+        }
+
+        let (provider, signer_address) = options
+            .signer
+            .connect(&options.eth_rpc, options.eth_chain_id)
+            .await?;
+        let contract = IBlobstream::new(options.eth_address, provider);
+        let (before, expected) = self.plan(&contract, signer_address).await?;
+
+        println!("contract: {}", options.eth_address);
+        println!("signer:   {}", signer_address);
+        println!("changes:");
+        before.print_diff(&expected);
+        if !options.yes && !confirm("Send transaction?")? {
+            println!("Aborted");
+            return Ok(());
+        }
+
+        let tx = TransactionRequest::default()
+            .with_to(options.eth_address)
+            .with_input(self.calldata(&expected));
+        let tx_hash = contract
+            .provider()
+            .send_transaction(tx)
+            .await
+            .map_err(|err| ContractError::from(alloy::contract::Error::from(err)))?
+            .watch()
+            .await
+            .map_err(ContractError::from)?;
+        tracing::debug!(target: "blobstream0::cli", "Admin transaction included: {}", tx_hash);
+        println!("transaction: {}", tx_hash);
+
+        let after = AdminState::read(&contract).await?;
+        println!("applied changes:");
+        before.print_diff(&after);
+        if after != expected {
+            // Other transactions, such as range updates, can change the state in the same block.
+            tracing::warn!(
+                target: "blobstream0::cli",
+                "Contract state does not match the expected changes"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Reads the contract state and the state expected after the transaction, checking that the
+    /// sender is allowed to send it.
+    async fn plan<P: Provider>(
+        &self,
+        contract: &IBlobstreamInstance<P>,
+        sender: Address,
+    ) -> anyhow::Result<(AdminState, AdminState)> {
+        let before = AdminState::read(contract).await?;
+        let mut expected = before.clone();
+        match self {
+            AdminCommand::SetTrustedState {
+                trusted_hash,
+                trusted_height,
+                ..
+            } => {
+                expected.latest_block_hash = *trusted_hash;
+                expected.latest_height = *trusted_height;
+            }
+            AdminCommand::SetImageId { image_id, .. } => {
+                expected.image_id = match image_id {
+                    Some(image_id) => *image_id,
+                    None => B256::from(<[u8; 32]>::from(light_client_image_id()?)),
+                };
+            }
+            AdminCommand::SetChainId { tm_chain_id, .. } => {
+                anyhow::ensure!(
+                    before.chain_id_hash.is_some(),
+                    "contract does not have a chain ID hash, upgrade it before setting one"
+                );
+                expected.chain_id_hash = Some(chain_id_hash(tm_chain_id).into());
+            }
+            AdminCommand::SetVerifier {
+                verifier_address, ..
+            } => {
+                anyhow::ensure!(
+                    !contract
+                        .provider()
+                        .get_code_at(*verifier_address)
+                        .await?
+                        .is_empty(),
+                    "no contract deployed at verifier address {}",
+                    verifier_address
+                );
+                expected.verifier = *verifier_address;
+            }
+            AdminCommand::TransferOwnership { new_owner, .. } => {
+                expected.pending_owner = *new_owner;
+            }
+            AdminCommand::AcceptOwnership { .. } => {
+                expected.owner = sender;
+                expected.pending_owner = Address::ZERO;
+            }
+        }
+
+        // Check the sender is allowed to send the transaction, to fail before confirmation.
+        if let AdminCommand::AcceptOwnership { .. } = self {
+            anyhow::ensure!(
+                before.pending_owner == sender,
+                "sender {} is not the pending owner {}",
+                sender,
+                before.pending_owner
+            );
+        } else {
+            anyhow::ensure!(
+                before.owner == sender,
+                "sender {} is not the contract owner {}",
+                sender,
+                before.owner
+            );
+        }
+
+        Ok((before, expected))
+    }
+
+    /// Calldata of the contract call that changes the state to `expected`.
+    fn calldata(&self, expected: &AdminState) -> Bytes {
+        match self {
+            AdminCommand::SetTrustedState { .. } => IBlobstream::adminSetTrustedStateCall {
+                _trustedHash: expected.latest_block_hash,
+                _trustedHeight: expected.latest_height,
+            }
+            .abi_encode(),
+            AdminCommand::SetImageId { .. } => IBlobstream::adminSetImageIdCall {
+                _imageId: expected.image_id,
+            }
+            .abi_encode(),
+            AdminCommand::SetChainId { tm_chain_id, .. } => IBlobstream::adminSetChainIdHashCall {
+                _chainIdHash: chain_id_hash(tm_chain_id).into(),
+            }
+            .abi_encode(),
+            AdminCommand::SetVerifier { .. } => IBlobstream::adminSetVerifierCall {
+                _verifier: expected.verifier,
+            }
+            .abi_encode(),
+            AdminCommand::TransferOwnership { .. } => IBlobstream::transferOwnershipCall {
+                newOwner: expected.pending_owner,
+            }
+            .abi_encode(),
+            AdminCommand::AcceptOwnership { .. } => {
+                IBlobstream::acceptOwnershipCall {}.abi_encode()
+            }
+        }
+        .into()
+    }
+}
+
+/// Whether the call reverted or returned no data, as a proxy does for functions its implementation
+/// does not have.
+pub(crate) fn is_revert(err: &alloy::contract::Error) -> bool {
+    matches!(err, alloy::contract::Error::ZeroData(..)) || err.as_revert_data().is_some()
+}
+
+/// Asks for confirmation on stdin, defaulting to no.
+fn confirm(prompt: &str) -> anyhow::Result<bool> {
+    print!("{} [y/N] ", prompt);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
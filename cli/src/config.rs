@@ -0,0 +1,449 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! TOML configuration file with named profiles.
+//!
+//! Profile values are applied as defaults for the environment variables the CLI arguments are
+//! read from, so that environment variables and flags always take precedence over the file.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use clap::{Args, Subcommand};
+use serde::Deserialize;
+
+use crate::manifest;
+
+/// Environment variable to read the config file path from, if `--config` is not passed.
+const CONFIG_ENV: &str = "BLOBSTREAM0_CONFIG";
+
+/// Environment variable to read the profile name from, if `--profile` is not passed.
+const PROFILE_ENV: &str = "BLOBSTREAM0_PROFILE";
+
+/// Environment variable that can be set from a profile or the deployment manifest.
+#[derive(Debug, Clone, Copy)]
+struct ConfigVar {
+    name: &'static str,
+    /// Whether the value is never printed in full.
+    secret: bool,
+}
+
+impl ConfigVar {
+    const fn public(name: &'static str) -> Self {
+        Self {
+            name,
+            secret: false,
+        }
+    }
+
+    const fn secret(name: &'static str) -> Self {
+        Self { name, secret: true }
+    }
+
+    /// Value as printed by `config check`.
+    fn display<'a>(&self, value: &'a str) -> &'a str {
+        if self.secret {
+            "<redacted>"
+        } else {
+            value
+        }
+    }
+}
+
+/// Environment variables printed by `config check`. Plaintext keys cannot be set from a profile,
+/// but are still listed to show when they are set in the environment.
+const CONFIG_VARS: &[ConfigVar] = &[
+    ConfigVar::public("ETH_CHAIN_ID"),
+    ConfigVar::public("ETH_RPC"),
+    ConfigVar::public("TENDERMINT_RPC"),
+    ConfigVar::public("ETH_ADDRESS"),
+    ConfigVar::public("PROXY_ADDRESS"),
+    ConfigVar::public("BATCH_SIZE"),
+    ConfigVar::public("MIN_BATCH_SIZE"),
+    ConfigVar::public("CATCH_UP_PARALLELISM"),
+    ConfigVar::public("LEAF_CONTENT"),
+    ConfigVar::public("PROTO_VERSION"),
+    ConfigVar::public("FROM_BLOCK"),
+    ConfigVar::public("INDEX_DB"),
+    ConfigVar::public("KEYSTORE"),
+    ConfigVar::public("KEYSTORE_PASSWORD_FILE"),
+    ConfigVar::public("PRIVATE_KEY_FILE"),
+    ConfigVar::secret("PRIVATE_KEY_HEX"),
+    ConfigVar::public("ALLOW_PLAINTEXT_KEY"),
+    ConfigVar::public("FIREBLOCKS_ADDRESS"),
+    ConfigVar::public("RISC0_DEV_MODE"),
+    ConfigVar::public("RISC0_PROVER"),
+    ConfigVar::public("BONSAI_API_URL"),
+    ConfigVar::secret("BONSAI_API_KEY"),
+];
+
+/// Arguments to select the configuration file and profile. These are parsed before the rest of
+/// the command line, so are only declared here for `--help` output and validation.
+#[derive(Args, Debug)]
+#[allow(dead_code)]
+pub(crate) struct ConfigArgs {
+    /// Path to a TOML configuration file with named profiles
+    #[clap(long, global = true, env = CONFIG_ENV)]
+    config: Option<PathBuf>,
+
+    /// Profile to use from the configuration file. Defaults to `default_profile` from the file.
+    #[clap(long, global = true, env = PROFILE_ENV)]
+    profile: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    /// Profile used when none is selected with `--profile`.
+    default_profile: Option<String>,
+    #[serde(default)]
+    profile: BTreeMap<String, Profile>,
+}
+
+/// Named set of values for a single deployment.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct Profile {
+    /// Expected chain ID of the Ethereum RPC.
+    eth_chain_id: Option<u64>,
+    eth_rpc: Option<String>,
+    tendermint_rpc: Option<String>,
+    /// Address of the Blobstream0 proxy contract.
+    eth_address: Option<String>,
+    batch_size: Option<u64>,
+    min_batch_size: Option<u64>,
+    catch_up_parallelism: Option<usize>,
+    /// Header field committed for each block, one of data-hash, app-hash or header-hash.
+    leaf_content: Option<String>,
+    /// CometBFT protobuf version of the Tendermint chain, one of 0.34, 0.37 or 0.38.
+    proto_version: Option<String>,
+    /// Ethereum block the contract was deployed in, to start searching for events from.
+    from_block: Option<u64>,
+    /// Path to the database of indexed contract events.
+    index_db: Option<String>,
+    keystore: Option<String>,
+    keystore_password_file: Option<String>,
+    private_key_file: Option<String>,
+    fireblocks_address: Option<String>,
+    prover: Option<ProverBackend>,
+    bonsai_api_url: Option<String>,
+    bonsai_api_key: Option<String>,
+}
+
+/// Backend used to generate proofs.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum ProverBackend {
+    /// Generate fake receipts, only accepted by the mock verifier.
+    Dev,
+    /// Prove on the local machine.
+    Local,
+    /// Prove remotely with Bonsai.
+    Bonsai,
+}
+
+impl Profile {
+    /// Environment variables the profile provides values for.
+    fn env_values(&self) -> Vec<(&'static str, String)> {
+        let prover = self.prover.map(|prover| match prover {
+            ProverBackend::Dev => ("RISC0_DEV_MODE", "true"),
+            ProverBackend::Local => ("RISC0_PROVER", "local"),
+            ProverBackend::Bonsai => ("RISC0_PROVER", "bonsai"),
+        });
+        [
+            ("ETH_CHAIN_ID", self.eth_chain_id.map(|v| v.to_string())),
+            ("ETH_RPC", self.eth_rpc.clone()),
+            ("TENDERMINT_RPC", self.tendermint_rpc.clone()),
+            ("ETH_ADDRESS", self.eth_address.clone()),
+            // The upgrade command refers to the same contract as the proxy address.
+            ("PROXY_ADDRESS", self.eth_address.clone()),
+            ("BATCH_SIZE", self.batch_size.map(|v| v.to_string())),
+            ("MIN_BATCH_SIZE", self.min_batch_size.map(|v| v.to_string())),
+            (
+                "CATCH_UP_PARALLELISM",
+                self.catch_up_parallelism.map(|v| v.to_string()),
+            ),
+            ("LEAF_CONTENT", self.leaf_content.clone()),
+            ("PROTO_VERSION", self.proto_version.clone()),
+            ("FROM_BLOCK", self.from_block.map(|v| v.to_string())),
+            ("INDEX_DB", self.index_db.clone()),
+            ("KEYSTORE", self.keystore.clone()),
+            (
+                "KEYSTORE_PASSWORD_FILE",
+                self.keystore_password_file.clone(),
+            ),
+            ("PRIVATE_KEY_FILE", self.private_key_file.clone()),
+            ("FIREBLOCKS_ADDRESS", self.fireblocks_address.clone()),
+            ("BONSAI_API_URL", self.bonsai_api_url.clone()),
+            ("BONSAI_API_KEY", self.bonsai_api_key.clone()),
+        ]
+        .into_iter()
+        .chain(prover.map(|(var, value)| (var, Some(value.to_string()))))
+        .filter_map(|(var, value)| Some((var, value?)))
+        .collect()
+    }
+}
+
+/// Configuration after applying the selected profile to the environment.
+#[derive(Debug, Default)]
+pub(crate) struct ResolvedConfig {
+    path: Option<PathBuf>,
+    profile: Option<String>,
+    /// Environment variables that were set from the profile.
+    from_file: BTreeSet<&'static str>,
+    manifest: Option<PathBuf>,
+    /// Environment variables that were set from the deployment manifest.
+    from_manifest: BTreeSet<&'static str>,
+}
+
+/// Source an environment variable default was taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Manifest,
+    ConfigFile,
+}
+
+/// Selects the defaults to set for environment variables which are not already set, with values
+/// from the manifest taking precedence over the profile.
+fn resolve_defaults(
+    is_set: impl Fn(&str) -> bool,
+    manifest: Vec<(&'static str, String)>,
+    profile: Vec<(&'static str, String)>,
+) -> BTreeMap<&'static str, (String, Source)> {
+    let mut defaults = BTreeMap::new();
+    let sources = [(manifest, Source::Manifest), (profile, Source::ConfigFile)];
+    for (values, source) in sources {
+        for (var, value) in values {
+            if !is_set(var) && !defaults.contains_key(var) {
+                defaults.insert(var, (value, source));
+            }
+        }
+    }
+    defaults
+}
+
+/// Loads the configuration file selected by `--config` or `BLOBSTREAM0_CONFIG`, and sets the
+/// values of the selected profile for all environment variables that are not already set.
+/// Values from the deployment manifest selected with `--manifest` take precedence over the
+/// profile.
+///
+/// Must be called before any other threads are started, as it modifies the environment.
+pub(crate) fn load_from_args() -> anyhow::Result<ResolvedConfig> {
+    let args: Vec<String> = env::args().collect();
+    let manifest = manifest::path_from_args(&args);
+    let manifest_values = manifest::env_values(manifest.as_deref())?;
+
+    let path = flag_value(&args, "--config")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os(CONFIG_ENV).map(PathBuf::from));
+    let (profile_name, profile) = match &path {
+        Some(path) => {
+            let (name, profile) = read_profile(path, flag_value(&args, "--profile"))?;
+            (name, Some(profile))
+        }
+        None => (None, None),
+    };
+    let profile_values = profile.map(|p| p.env_values()).unwrap_or_default();
+
+    let defaults = resolve_defaults(
+        |var| env::var_os(var).is_some(),
+        manifest_values,
+        profile_values,
+    );
+    let mut resolved = ResolvedConfig {
+        path,
+        profile: profile_name,
+        manifest,
+        ..Default::default()
+    };
+    for (var, (value, source)) in defaults {
+        env::set_var(var, value);
+        match source {
+            Source::Manifest => resolved.from_manifest.insert(var),
+            Source::ConfigFile => resolved.from_file.insert(var),
+        };
+    }
+    Ok(resolved)
+}
+
+/// Reads the profile selected by `--profile`, `BLOBSTREAM0_PROFILE` or the default of the file.
+fn read_profile(
+    path: &Path,
+    profile_flag: Option<String>,
+) -> anyhow::Result<(Option<String>, Profile)> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read config file {}", path.display()))?;
+    let mut file: ConfigFile = toml::from_str(&contents)
+        .with_context(|| format!("failed to parse config file {}", path.display()))?;
+
+    let profile_name = profile_flag
+        .or_else(|| env::var(PROFILE_ENV).ok())
+        .or(file.default_profile.take());
+    let profile = match &profile_name {
+        Some(name) => file
+            .profile
+            .remove(name)
+            .with_context(|| format!("profile {name} not found in {}", path.display()))?,
+        None if file.profile.is_empty() => Profile::default(),
+        None => bail!(
+            "no profile selected, use --profile or set default_profile in {}",
+            path.display()
+        ),
+    };
+    Ok((profile_name, profile))
+}
+
+/// Finds the value of a flag passed as either `--flag value` or `--flag=value`.
+pub(crate) fn flag_value(args: &[String], flag: &str) -> Option<String> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == flag {
+            return iter.next().cloned();
+        }
+        if let Some(value) = arg
+            .strip_prefix(flag)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum ConfigCommand {
+    /// Print the resolved configuration, with secrets redacted.
+    Check,
+}
+
+impl ConfigCommand {
+    pub(crate) fn run(self, resolved: &ResolvedConfig) -> anyhow::Result<()> {
+        match self {
+            ConfigCommand::Check => {
+                match &resolved.path {
+                    Some(path) => println!("config file: {}", path.display()),
+                    None => println!("config file: none"),
+                }
+                println!("profile: {}", resolved.profile.as_deref().unwrap_or("none"));
+                match &resolved.manifest {
+                    Some(path) => println!("manifest: {}", path.display()),
+                    None => println!("manifest: none"),
+                }
+
+                for var in CONFIG_VARS {
+                    let Ok(value) = env::var(var.name) else {
+                        continue;
+                    };
+                    let source = if resolved.from_manifest.contains(var.name) {
+                        "manifest"
+                    } else if resolved.from_file.contains(var.name) {
+                        "config file"
+                    } else {
+                        "environment"
+                    };
+                    println!("{}={} ({source})", var.name, var.display(&value));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn flag_value_forms() {
+        let separate = args(&["blobstream0-cli", "--profile", "sepolia", "status"]);
+        assert_eq!(
+            flag_value(&separate, "--profile").as_deref(),
+            Some("sepolia")
+        );
+
+        let joined = args(&["blobstream0-cli", "--profile=sepolia", "status"]);
+        assert_eq!(flag_value(&joined, "--profile").as_deref(), Some("sepolia"));
+
+        // Flags sharing a prefix are not matched.
+        let prefixed = args(&["blobstream0-cli", "--profiles=sepolia"]);
+        assert_eq!(flag_value(&prefixed, "--profile"), None);
+
+        let missing_value = args(&["blobstream0-cli", "--profile"]);
+        assert_eq!(flag_value(&missing_value, "--profile"), None);
+    }
+
+    #[test]
+    fn precedence() {
+        let manifest = vec![
+            ("ETH_ADDRESS", "manifest".to_string()),
+            ("FROM_BLOCK", "manifest".to_string()),
+        ];
+        let profile = vec![
+            ("ETH_ADDRESS", "profile".to_string()),
+            ("FROM_BLOCK", "profile".to_string()),
+            ("ETH_RPC", "profile".to_string()),
+            ("BATCH_SIZE", "profile".to_string()),
+        ];
+        let defaults = resolve_defaults(|var| var == "FROM_BLOCK", manifest, profile);
+
+        // Set in the environment, so not overridden by the manifest or profile.
+        assert!(!defaults.contains_key("FROM_BLOCK"));
+        assert_eq!(
+            defaults["ETH_ADDRESS"],
+            ("manifest".to_string(), Source::Manifest)
+        );
+        assert_eq!(
+            defaults["ETH_RPC"],
+            ("profile".to_string(), Source::ConfigFile)
+        );
+        assert_eq!(defaults.len(), 3);
+    }
+
+    #[test]
+    fn redaction() {
+        let secrets: Vec<_> = CONFIG_VARS
+            .iter()
+            .filter(|var| var.secret)
+            .map(|var| var.name)
+            .collect();
+        assert_eq!(secrets, ["PRIVATE_KEY_HEX", "BONSAI_API_KEY"]);
+
+        let key = CONFIG_VARS
+            .iter()
+            .find(|var| var.name == "BONSAI_API_KEY")
+            .unwrap();
+        assert_eq!(key.display("hunter2"), "<redacted>");
+        assert_eq!(
+            ConfigVar::public("ETH_RPC").display("http://rpc"),
+            "http://rpc"
+        );
+    }
+
+    #[test]
+    fn profile_rejects_plaintext_key() {
+        let contents = r#"
+            [profile.local]
+            private_key_hex = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+        "#;
+        assert!(toml::from_str::<ConfigFile>(contents).is_err());
+    }
+}
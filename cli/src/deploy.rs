@@ -0,0 +1,923 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deployment of the verifier and Blobstream0 contracts, and upgrades of the proxy.
+
+use std::path::{Path, PathBuf};
+
+use alloy::{
+    contract::RawCallBuilder,
+    hex::FromHex,
+    network::TransactionBuilder,
+    primitives::{address, b256, Address, Bytes, FixedBytes, TxHash, B256, U256},
+    providers::Provider,
+    rpc::types::TransactionRequest,
+};
+use alloy_sol_types::{sol, SolCall, SolConstructor};
+use anyhow::Context;
+use blobstream0_core::{light_client_image_id, tendermint_client, ContractError};
+use blobstream0_primitives::{chain_id_hash, proto::ProtoVersion, IBlobstream, LeafContent};
+use clap::Parser;
+use tendermint::chain;
+use tendermint_rpc::Client;
+
+use crate::{
+    admin::is_revert,
+    manifest::{DeploymentManifest, DeploymentTransactions, UpgradeTransactions},
+    safe::{self, SafeExportArgs, SafeTransaction},
+    signer,
+    status::{detect_verifier, VerifierKind},
+    storage_layout::StorageLayout,
+    ERC1967Proxy, MockVerifier, RiscZeroGroth16Verifier, BN254_CONTROL_ID, CONTROL_ID,
+};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub(crate) struct DeployArgs {
+    /// The Ethereum RPC URL
+    #[clap(long, env)]
+    eth_rpc: String,
+
+    /// Expected chain ID of the Ethereum RPC, checked before sending any transactions
+    #[clap(long, env)]
+    eth_chain_id: Option<u64>,
+
+    #[clap(flatten)]
+    signer: signer::SignerArgs,
+
+    /// Hex encoded address of admin for upgrades. Will default to the private key address.
+    #[clap(long, env)]
+    admin_address: Option<String>,
+
+    /// Address of risc0 verifier to use (either mock or groth16)
+    #[clap(long, env)]
+    verifier_address: Option<String>,
+
+    /// The Tendermint RPC URL, to fetch the trusted block hash from. If set, the hash given with
+    /// `--tm-block-hash` is checked against the fetched hash.
+    #[clap(long, env)]
+    tendermint_rpc: Option<String>,
+
+    /// CometBFT protobuf version of the Tendermint chain: 0.34, 0.37 or 0.38
+    #[clap(long, env, default_value_t)]
+    proto_version: ProtoVersion,
+
+    /// Trusted height for contract. Defaults to the latest height of `--tendermint-rpc`.
+    #[clap(long, env)]
+    tm_height: Option<u64>,
+
+    /// Trusted block hash for contract. Fetched from `--tendermint-rpc` if not set.
+    #[clap(long, env)]
+    tm_block_hash: Option<String>,
+
+    /// Tendermint chain ID the contract accepts proofs for. Fetched from `--tendermint-rpc` if not
+    /// set.
+    #[clap(long, env)]
+    tm_chain_id: Option<chain::Id>,
+
+    /// Header field the contract accepts commitments to for each block: data-hash, app-hash or
+    /// header-hash. Celestia commits to the data hash, which is the data root of the block.
+    #[clap(long, env, default_value_t)]
+    leaf_content: LeafContent,
+
+    /// Minimum batch size for head updates. The batch size must be larger than this value.
+    #[clap(long, env)]
+    min_batch_size: u64,
+
+    /// If deploying verifier, will it deploy the mock verifier
+    #[clap(long)]
+    dev: bool,
+
+    /// Salt to deploy the contracts with CREATE2 through the deterministic deployment proxy. The
+    /// addresses then only depend on the salt and the verifier, so are the same on every chain.
+    #[clap(long, env)]
+    create2_salt: Option<B256>,
+
+    /// Deterministic deployment proxy used with `--create2-salt`
+    #[clap(long, env, default_value_t = DETERMINISTIC_DEPLOYER)]
+    create2_deployer: Address,
+
+    /// Multicall3 contract the proxy is deployed and initialized through with `--create2-salt`
+    #[clap(long, env, default_value_t = MULTICALL3)]
+    create2_multicall: Address,
+}
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub(crate) struct UpgradeArgs {
+    /// The Ethereum RPC URL
+    #[clap(long, env)]
+    eth_rpc: String,
+
+    /// Expected chain ID of the Ethereum RPC, checked before sending any transactions
+    #[clap(long, env)]
+    eth_chain_id: Option<u64>,
+
+    #[clap(flatten)]
+    signer: signer::SignerArgs,
+
+    /// Hex encoded address of admin for upgrades. Will default to the private key address.
+    #[clap(long, env)]
+    proxy_address: String,
+
+    /// Forge artifact of the deployed implementation, to check the storage layout of the new
+    /// implementation against. Required unless the manifest records the storage layout.
+    #[clap(long, env)]
+    previous_artifact: Option<PathBuf>,
+
+    /// Set the image ID to the light client guest built with this CLI in the upgrade transaction
+    #[clap(long)]
+    set_image_id: bool,
+
+    /// Tendermint chain ID to set in the upgrade transaction. Required if the contract has no
+    /// chain ID hash set, as deployments initialized before it was committed reject all proofs
+    /// until it is.
+    #[clap(long, env)]
+    tm_chain_id: Option<chain::Id>,
+
+    /// Header field committed for each block to set in the upgrade transaction. Deployments
+    /// initialized before it was committed accept the data hash until it is set.
+    #[clap(long, env)]
+    leaf_content: Option<LeafContent>,
+
+    // The new implementation is still deployed with the signer when exporting for a Safe.
+    #[clap(flatten)]
+    safe: SafeExportArgs,
+}
+
+/// Forge artifact of the Blobstream0 contract the new implementation is deployed from.
+const BLOBSTREAM0_ARTIFACT: &str = include_str!("../../contracts/artifacts/Blobstream0.json");
+
+/// Storage slot of the implementation address of ERC1967 proxies, which UUPS implementations
+/// return from `proxiableUUID`.
+const ERC1967_IMPLEMENTATION_SLOT: B256 =
+    b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+
+/// Deterministic deployment proxy, deployed at the same address on most EVM chains.
+/// See <https://github.com/Arachnid/deterministic-deployment-proxy>.
+const DETERMINISTIC_DEPLOYER: Address = address!("4e59b44847b379578588920cA78FbF26c0B4956C");
+
+/// Multicall3, deployed at the same address on most EVM chains.
+/// See <https://github.com/mds1/multicall>.
+const MULTICALL3: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+sol! {
+    #[sol(rpc)]
+    interface IMulticall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Result {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls) external payable returns (Result[] memory returnData);
+    }
+}
+
+/// Contract deployed by a transaction.
+struct Deployed {
+    address: Address,
+    tx_hash: TxHash,
+    block_number: u64,
+}
+
+/// How contracts are deployed.
+enum Deployer {
+    /// Deploy with CREATE, so the addresses depend on the sender and its nonce.
+    Create,
+    /// Deploy with CREATE2 through a deterministic deployment proxy, which deploys the init code
+    /// appended to the salt in the calldata. The proxy is deployed and initialized in one
+    /// transaction through Multicall3.
+    Create2 {
+        deployer: Address,
+        salt: B256,
+        multicall: Address,
+    },
+}
+
+impl Deployer {
+    /// Checks the deterministic deployment proxy and Multicall3 exist on the chain.
+    async fn check<P: Provider>(&self, provider: &P) -> anyhow::Result<()> {
+        if let Deployer::Create2 {
+            deployer,
+            multicall,
+            ..
+        } = self
+        {
+            anyhow::ensure!(
+                !provider.get_code_at(*deployer).await?.is_empty(),
+                "no deterministic deployment proxy at {}",
+                deployer
+            );
+            anyhow::ensure!(
+                !provider.get_code_at(*multicall).await?.is_empty(),
+                "no Multicall3 at {}",
+                multicall
+            );
+        }
+        Ok(())
+    }
+
+    /// Sends a contract deployment and waits for it to be included.
+    async fn deploy<P: Provider>(
+        &self,
+        provider: &P,
+        builder: RawCallBuilder<&P>,
+    ) -> anyhow::Result<Deployed> {
+        let Deployer::Create2 { deployer, salt, .. } = self else {
+            return send_deploy(builder).await;
+        };
+
+        let address = deployer.create2_from_code(salt.0, builder.calldata());
+        anyhow::ensure!(
+            provider.get_code_at(address).await?.is_empty(),
+            "a contract is already deployed at {}, use a different --create2-salt",
+            address
+        );
+        let tx = TransactionRequest::default()
+            .with_to(*deployer)
+            .with_input([salt.as_slice(), builder.calldata()].concat());
+        let receipt = provider
+            .send_transaction(tx)
+            .await?
+            .get_receipt()
+            .await
+            .map_err(ContractError::from)?;
+        anyhow::ensure!(
+            receipt.status() && !provider.get_code_at(address).await?.is_empty(),
+            "CREATE2 deployment transaction {} did not deploy to {}",
+            receipt.transaction_hash,
+            address
+        );
+        Ok(Deployed {
+            address,
+            tx_hash: receipt.transaction_hash,
+            block_number: receipt.block_number.unwrap_or_default(),
+        })
+    }
+
+    /// Deploys an ERC1967 proxy of `implementation`, initialized with the `initialize` calldata,
+    /// and waits for it to be included.
+    async fn deploy_proxy<P: Provider>(
+        &self,
+        provider: &P,
+        implementation: Address,
+        initialize: Bytes,
+    ) -> anyhow::Result<Deployed> {
+        let Deployer::Create2 {
+            deployer,
+            salt,
+            multicall,
+        } = self
+        else {
+            return send_deploy(ERC1967Proxy::deploy_builder(
+                provider,
+                implementation,
+                initialize,
+            ))
+            .await;
+        };
+
+        let (address, calls) = create2_proxy_calls(*deployer, *salt, implementation, initialize);
+        anyhow::ensure!(
+            provider.get_code_at(address).await?.is_empty(),
+            "a contract is already deployed at {}, use a different --create2-salt",
+            address
+        );
+        let receipt = IMulticall3::new(*multicall, provider)
+            .aggregate3(calls)
+            .send()
+            .await
+            .map_err(ContractError::from)?
+            .get_receipt()
+            .await
+            .map_err(ContractError::from)?;
+        anyhow::ensure!(
+            receipt.status() && !provider.get_code_at(address).await?.is_empty(),
+            "CREATE2 proxy deployment transaction {} did not deploy to {}",
+            receipt.transaction_hash,
+            address
+        );
+        Ok(Deployed {
+            address,
+            tx_hash: receipt.transaction_hash,
+            block_number: receipt.block_number.unwrap_or_default(),
+        })
+    }
+}
+
+/// Init code of an ERC1967 proxy of `implementation` that is not initialized on deployment.
+fn proxy_init_code(implementation: Address) -> Vec<u8> {
+    let constructor = ERC1967Proxy::constructorCall::new((implementation, Bytes::new()));
+    [ERC1967Proxy::BYTECODE.as_ref(), &constructor.abi_encode()].concat()
+}
+
+/// Multicall3 calls deploying an ERC1967 proxy of `implementation` with CREATE2 through
+/// `deployer`, then initializing it with the `initialize` calldata, with the address of the proxy.
+///
+/// The proxy is deployed without the initialize calldata, which holds the trusted state and
+/// Tendermint chain ID, so its address is the same on every chain. Initializing it in the same
+/// transaction leaves no window for anyone else to initialize it.
+fn create2_proxy_calls(
+    deployer: Address,
+    salt: B256,
+    implementation: Address,
+    initialize: Bytes,
+) -> (Address, Vec<IMulticall3::Call3>) {
+    let init_code = proxy_init_code(implementation);
+    let address = deployer.create2_from_code(salt.0, &init_code);
+    let calls = vec![
+        IMulticall3::Call3 {
+            target: deployer,
+            allowFailure: false,
+            callData: [salt.as_slice(), &init_code].concat().into(),
+        },
+        IMulticall3::Call3 {
+            target: address,
+            allowFailure: false,
+            callData: initialize,
+        },
+    ];
+    (address, calls)
+}
+
+/// Sends a contract deployment with CREATE and waits for it to be included.
+async fn send_deploy<P: Provider>(builder: RawCallBuilder<P>) -> anyhow::Result<Deployed> {
+    let receipt = builder
+        .send()
+        .await
+        .map_err(ContractError::from)?
+        .get_receipt()
+        .await
+        .map_err(ContractError::from)?;
+    anyhow::ensure!(
+        receipt.status(),
+        "deployment transaction {} reverted",
+        receipt.transaction_hash
+    );
+    Ok(Deployed {
+        address: receipt
+            .contract_address
+            .ok_or_else(|| anyhow::anyhow!("deployment receipt has no contract address"))?,
+        tx_hash: receipt.transaction_hash,
+        block_number: receipt.block_number.unwrap_or_default(),
+    })
+}
+
+impl DeployArgs {
+    /// Deploys the contracts, writing the deployment manifest to `manifest` if set.
+    pub(crate) async fn run(self, manifest: Option<&Path>) -> anyhow::Result<()> {
+        if let Some(path) = manifest {
+            // Checked before deploying, as the manifest is only written after.
+            anyhow::ensure!(
+                !path.exists(),
+                "manifest {} already exists, use a new path for a new deployment",
+                path.display()
+            );
+        }
+
+        let (trusted_height, trusted_hash, tm_chain_id) = trusted_block(
+            self.tendermint_rpc.as_deref(),
+            self.proto_version,
+            self.tm_height,
+            self.tm_block_hash.as_deref(),
+            self.tm_chain_id,
+        )
+        .await?;
+        println!(
+            "trusted block: {} at height {} of {}",
+            trusted_hash, trusted_height, tm_chain_id
+        );
+
+        let (provider, signer_address) = self
+            .signer
+            .connect(&self.eth_rpc, self.eth_chain_id)
+            .await?;
+        let admin_address: Address = if let Some(address) = self.admin_address {
+            address.parse()?
+        } else {
+            signer_address
+        };
+
+        let deployer = match self.create2_salt {
+            Some(salt) => Deployer::Create2 {
+                deployer: self.create2_deployer,
+                salt,
+                multicall: self.create2_multicall,
+            },
+            None => Deployer::Create,
+        };
+        deployer.check(&provider).await?;
+
+        let given_verifier: Option<Address> = self
+            .verifier_address
+            .as_deref()
+            .map(str::parse)
+            .transpose()?;
+
+        let verifier_deploy = match given_verifier {
+            Some(_) => None,
+            None if self.dev => Some((
+                MockVerifier::deploy_builder(&provider, [0, 0, 0, 0].into()),
+                VerifierKind::Mock,
+            )),
+            None => Some((
+                RiscZeroGroth16Verifier::deploy_builder(
+                    &provider,
+                    CONTROL_ID.into(),
+                    BN254_CONTROL_ID.into(),
+                ),
+                VerifierKind::Groth16,
+            )),
+        };
+        let implementation_deploy = IBlobstream::deploy_builder(&provider);
+        let initialize = |verifier: Address| -> Bytes {
+            IBlobstream::initializeCall {
+                _admin: admin_address,
+                _verifier: verifier,
+                _trustedHash: trusted_hash,
+                _trustedHeight: trusted_height,
+                _minBatchSize: self.min_batch_size,
+                _chainIdHash: chain_id_hash(&tm_chain_id).into(),
+                _leafContent: self.leaf_content.into(),
+            }
+            .abi_encode()
+            .into()
+        };
+
+        // With CREATE2 the addresses only depend on the init code, so are known before sending.
+        if let Deployer::Create2 {
+            deployer: create2_deployer,
+            salt,
+            ..
+        } = &deployer
+        {
+            let predict = |builder: &RawCallBuilder<_>| {
+                create2_deployer.create2_from_code(salt.0, builder.calldata())
+            };
+            let verifier = match (given_verifier, &verifier_deploy) {
+                (Some(address), _) => address,
+                (None, Some((builder, _))) => predict(builder),
+                (None, None) => unreachable!("verifier is deployed when no address is given"),
+            };
+            let implementation = predict(&implementation_deploy);
+            let proxy = create2_deployer.create2_from_code(salt.0, proxy_init_code(implementation));
+            println!("predicted verifier address: {}", verifier);
+            println!("predicted implementation address: {}", implementation);
+            println!("predicted contract address: {}", proxy);
+        }
+
+        let (verifier_address, verifier_kind, verifier_tx) = match (given_verifier, verifier_deploy)
+        {
+            (Some(address), _) => (address, detect_verifier(&provider, address).await, None),
+            (None, Some((builder, kind))) => {
+                tracing::debug!(target: "blobstream0::cli", "Deploying {:?} verifier", kind);
+                let deployed = deployer.deploy(&provider, builder).await?;
+                println!("deployed verifier to address: {}", deployed.address);
+                (deployed.address, kind, Some(deployed.tx_hash))
+            }
+            (None, None) => unreachable!("verifier is deployed when no address is given"),
+        };
+
+        // Deploy the contract.
+        let implementation = deployer.deploy(&provider, implementation_deploy).await?;
+        tracing::debug!(target: "blobstream0::cli", "Deployed implementation contract to {}", implementation.address);
+
+        let proxy = deployer
+            .deploy_proxy(
+                &provider,
+                implementation.address,
+                initialize(verifier_address),
+            )
+            .await?;
+        tracing::debug!(target: "blobstream0::cli", "Deployed proxy contract");
+
+        println!("deployed contract to address: {}", proxy.address);
+
+        if let Some(path) = manifest {
+            let deployment = DeploymentManifest {
+                chain_id: provider.get_chain_id().await?,
+                proxy_address: proxy.address,
+                implementation_address: implementation.address,
+                verifier_address,
+                verifier_kind,
+                image_id: IBlobstream::new(proxy.address, &provider)
+                    .imageId()
+                    .call()
+                    .await?,
+                trusted_height,
+                trusted_hash,
+                tm_chain_id: Some(tm_chain_id.to_string()),
+                leaf_content: Some(self.leaf_content.to_string()),
+                min_batch_size: self.min_batch_size,
+                admin: admin_address,
+                deploy_block: proxy.block_number,
+                create2_salt: self.create2_salt,
+                storage_layout: StorageLayout::from_artifact(BLOBSTREAM0_ARTIFACT)?,
+                transactions: DeploymentTransactions {
+                    verifier: verifier_tx,
+                    implementation: implementation.tx_hash,
+                    proxy: proxy.tx_hash,
+                    upgrades: Vec::new(),
+                },
+            };
+            deployment.create(path)?;
+            println!("wrote deployment manifest to {}", path.display());
+        }
+
+        Ok(())
+    }
+}
+
+impl UpgradeArgs {
+    /// Upgrades the proxy to a new implementation, recording the upgrade in `manifest` if set.
+    pub(crate) async fn run(self, manifest: Option<&Path>) -> anyhow::Result<()> {
+        let (provider, signer_address) = self
+            .signer
+            .connect(&self.eth_rpc, self.eth_chain_id)
+            .await?;
+
+        let proxy_address: Address = self.proxy_address.parse()?;
+        println!("proxy address: {}", proxy_address);
+
+        let mut deployment = match manifest {
+            Some(path) => {
+                let deployment = DeploymentManifest::read(path)?;
+                anyhow::ensure!(
+                    deployment.proxy_address == proxy_address,
+                    "proxy address {} does not match the manifest proxy address {}",
+                    proxy_address,
+                    deployment.proxy_address
+                );
+                let chain_id = provider.get_chain_id().await?;
+                anyhow::ensure!(
+                    deployment.chain_id == chain_id,
+                    "Ethereum RPC chain ID {} does not match the manifest chain ID {}",
+                    chain_id,
+                    deployment.chain_id
+                );
+                Some(deployment)
+            }
+            None => None,
+        };
+
+        let proxy = IBlobstream::new(proxy_address, provider.clone());
+        let owner = proxy.owner().call().await?;
+        let image_id = proxy.imageId().call().await?;
+        // When exporting for a Safe, the Safe is the owner that sends the upgrade.
+        let upgrader = match self.safe.target() {
+            Some((_, safe_address)) => safe_address,
+            None => signer_address,
+        };
+        anyhow::ensure!(
+            owner == upgrader,
+            "{} is not the contract owner {}",
+            upgrader,
+            owner
+        );
+
+        self.check_storage_layout(deployment.as_ref())?;
+
+        let local_image_id = B256::from(<[u8; 32]>::from(light_client_image_id()?));
+        let new_image_id = if local_image_id == image_id {
+            None
+        } else if self.set_image_id {
+            println!("image ID: {} -> {}", image_id, local_image_id);
+            Some(local_image_id)
+        } else {
+            tracing::warn!(
+                target: "blobstream0::cli",
+                "Image ID of the contract {} differs from the local guest {}, pass --set-image-id to \
+                 update it with the upgrade",
+                image_id,
+                local_image_id
+            );
+            None
+        };
+
+        // Implementations from before the chain ID hash was committed have no getter for it.
+        let current_chain_id_hash = match proxy.chainIdHash().call().await {
+            Ok(chain_id_hash) => chain_id_hash,
+            Err(e) if is_revert(&e) => B256::ZERO,
+            Err(e) => return Err(ContractError::from(e).into()),
+        };
+        let new_chain_id_hash = match &self.tm_chain_id {
+            Some(tm_chain_id) => {
+                let new_chain_id_hash = B256::from(chain_id_hash(tm_chain_id));
+                (new_chain_id_hash != current_chain_id_hash).then(|| {
+                    println!(
+                        "chain ID hash: {} -> {}",
+                        current_chain_id_hash, new_chain_id_hash
+                    );
+                    new_chain_id_hash
+                })
+            }
+            None => {
+                anyhow::ensure!(
+                    current_chain_id_hash != B256::ZERO,
+                    "chain ID hash of the contract is not set, pass --tm-chain-id to set it in the \
+                     upgrade transaction"
+                );
+                None
+            }
+        };
+
+        // Implementations from before the leaf content was committed only accept the data hash.
+        let current_leaf_content = match proxy.leafContent().call().await {
+            Ok(leaf_content) => LeafContent::try_from(leaf_content)?,
+            Err(e) if is_revert(&e) => LeafContent::DataHash,
+            Err(e) => return Err(ContractError::from(e).into()),
+        };
+        let new_leaf_content = self
+            .leaf_content
+            .filter(|leaf_content| *leaf_content != current_leaf_content);
+        if let Some(new_leaf_content) = new_leaf_content {
+            println!(
+                "leaf content: {} -> {}",
+                current_leaf_content, new_leaf_content
+            );
+        }
+
+        let implementation = send_deploy(IBlobstream::deploy_builder(&provider)).await?;
+        tracing::debug!(target: "blobstream0::cli", "Deployed new implementation contract to {}", implementation.address);
+
+        // The proxy checks this as well, but checking first avoids spending gas on a revert.
+        let uuid = IBlobstream::new(implementation.address, &provider)
+            .proxiableUUID()
+            .call()
+            .await
+            .map_err(ContractError::from)?;
+        anyhow::ensure!(
+            uuid == ERC1967_IMPLEMENTATION_SLOT,
+            "new implementation returned an invalid proxiableUUID {}",
+            uuid
+        );
+
+        // State set by the new implementation in the upgrade transaction, batched through
+        // multicall when there is more than one call.
+        let mut calls: Vec<Bytes> = Vec::new();
+        if let Some(new_image_id) = new_image_id {
+            calls.push(
+                IBlobstream::adminSetImageIdCall {
+                    _imageId: new_image_id,
+                }
+                .abi_encode()
+                .into(),
+            );
+        }
+        if let Some(new_chain_id_hash) = new_chain_id_hash {
+            calls.push(
+                IBlobstream::adminSetChainIdHashCall {
+                    _chainIdHash: new_chain_id_hash,
+                }
+                .abi_encode()
+                .into(),
+            );
+        }
+        if let Some(new_leaf_content) = new_leaf_content {
+            calls.push(
+                IBlobstream::adminSetLeafContentCall {
+                    _leafContent: new_leaf_content.into(),
+                }
+                .abi_encode()
+                .into(),
+            );
+        }
+        let call_data = match calls.len() {
+            0 => Bytes::new(),
+            1 => calls.remove(0),
+            _ => IBlobstream::multicallCall { data: calls }
+                .abi_encode()
+                .into(),
+        };
+
+        if let Some((path, safe_address)) = self.safe.target() {
+            let data = IBlobstream::upgradeToAndCallCall {
+                newImplementation: implementation.address,
+                data: call_data,
+            }
+            .abi_encode();
+            safe::write_batch(
+                path,
+                provider.get_chain_id().await?,
+                safe_address,
+                "Blobstream0 upgrade",
+                &format!(
+                    "Upgrade {} to implementation {}",
+                    proxy_address, implementation.address
+                ),
+                &[SafeTransaction {
+                    to: proxy_address,
+                    value: U256::ZERO,
+                    data: data.into(),
+                }],
+            )?;
+            // The manifest is updated by a later upgrade, once the Safe has executed this one.
+            return Ok(());
+        }
+
+        let upgrade_tx = proxy
+            .upgradeToAndCall(implementation.address, call_data)
+            .send()
+            .await
+            .map_err(ContractError::from)?
+            .watch()
+            .await
+            .map_err(ContractError::from)?;
+        tracing::debug!(target: "blobstream0::cli", "Upgraded proxy contract to new implementation");
+
+        let slot = provider
+            .get_storage_at(
+                proxy_address,
+                U256::from_be_bytes(ERC1967_IMPLEMENTATION_SLOT.0),
+            )
+            .await?;
+        anyhow::ensure!(
+            Address::from_word(B256::from(slot)) == implementation.address,
+            "proxy implementation slot does not point to the new implementation after the upgrade"
+        );
+        println!("Upgraded proxy contract to {}", implementation.address);
+
+        if let (Some(path), Some(deployment)) = (manifest, deployment.as_mut()) {
+            deployment.implementation_address = implementation.address;
+            deployment.storage_layout = StorageLayout::from_artifact(BLOBSTREAM0_ARTIFACT)?;
+            if let Some(new_image_id) = new_image_id {
+                deployment.image_id = new_image_id;
+            }
+            if let Some(tm_chain_id) = &self.tm_chain_id {
+                deployment.tm_chain_id = Some(tm_chain_id.to_string());
+            }
+            if let Some(leaf_content) = self.leaf_content {
+                deployment.leaf_content = Some(leaf_content.to_string());
+            }
+            deployment.transactions.upgrades.push(UpgradeTransactions {
+                implementation_address: implementation.address,
+                implementation: implementation.tx_hash,
+                upgrade: upgrade_tx,
+            });
+            deployment.update(path)?;
+            println!("updated deployment manifest {}", path.display());
+        }
+
+        Ok(())
+    }
+
+    /// Checks the storage layout of the new implementation against the deployed implementation,
+    /// from `--previous-artifact` or else the manifest, failing if any existing storage would be
+    /// moved or retyped or the deployed layout is unknown.
+    fn check_storage_layout(&self, deployment: Option<&DeploymentManifest>) -> anyhow::Result<()> {
+        let old_layout = match (&self.previous_artifact, deployment) {
+            (Some(previous_artifact), _) => {
+                let previous = std::fs::read_to_string(previous_artifact)
+                    .with_context(|| format!("failed to read {}", previous_artifact.display()))?;
+                StorageLayout::from_artifact(&previous)?
+            }
+            (None, Some(deployment)) => deployment.storage_layout.clone(),
+            (None, None) => None,
+        };
+        let Some(old_layout) = old_layout else {
+            anyhow::bail!(
+                "storage layout of the deployed implementation is unknown, pass --previous-artifact \
+                 with the forge artifact of the deployed implementation, built with the \
+                 `storageLayout` extra output"
+            );
+        };
+        let Some(new_layout) = StorageLayout::from_artifact(BLOBSTREAM0_ARTIFACT)? else {
+            anyhow::bail!(
+                "forge artifact is missing the storage layout, build the contracts with the \
+                 `storageLayout` extra output"
+            );
+        };
+
+        let diff = old_layout.compare(&new_layout);
+        for warning in &diff.warnings {
+            tracing::warn!(target: "blobstream0::cli", "Storage layout: {}", warning);
+        }
+        anyhow::ensure!(
+            diff.errors.is_empty(),
+            "new implementation is not storage compatible with the deployed implementation:\n{}",
+            diff.errors.join("\n")
+        );
+        Ok(())
+    }
+}
+
+/// Resolves the trusted block and chain ID for a deployment. When a Tendermint RPC is given, the
+/// block hash and chain ID are fetched at the trusted height (or the latest height) and checked
+/// against any given values.
+async fn trusted_block(
+    tendermint_rpc: Option<&str>,
+    proto_version: ProtoVersion,
+    height: Option<u64>,
+    block_hash: Option<&str>,
+    chain_id: Option<chain::Id>,
+) -> anyhow::Result<(u64, FixedBytes<32>, chain::Id)> {
+    let block_hash = block_hash.map(FixedBytes::<32>::from_hex).transpose()?;
+    let Some(tendermint_rpc) = tendermint_rpc else {
+        let (Some(height), Some(block_hash), Some(chain_id)) = (height, block_hash, chain_id)
+        else {
+            anyhow::bail!(
+                "--tm-height, --tm-block-hash and --tm-chain-id are required without \
+                 --tendermint-rpc"
+            );
+        };
+        return Ok((height, block_hash, chain_id));
+    };
+
+    let client = tendermint_client(tendermint_rpc, proto_version)?;
+    let height = match height {
+        Some(height) => height,
+        // Tendermint blocks are final once committed, so the latest block can be trusted.
+        None => client.status().await?.sync_info.latest_block_height.value(),
+    };
+    let header = client.header(u32::try_from(height)?).await?.header;
+    let fetched_hash = FixedBytes::<32>::from_slice(header.hash().as_bytes());
+    tracing::debug!(target: "blobstream0::cli", "Fetched block hash {} at height {}", fetched_hash, height);
+
+    if let Some(block_hash) = block_hash {
+        anyhow::ensure!(
+            block_hash == fetched_hash,
+            "--tm-block-hash {} does not match the hash {} of height {} from {}",
+            block_hash,
+            fetched_hash,
+            height,
+            tendermint_rpc
+        );
+    }
+    if let Some(chain_id) = chain_id {
+        anyhow::ensure!(
+            chain_id == header.chain_id,
+            "--tm-chain-id {} does not match the chain ID {} from {}",
+            chain_id,
+            header.chain_id,
+            tendermint_rpc
+        );
+    }
+    Ok((height, fetched_hash, header.chain_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Calldata of `initialize` for a deployment tracking `chain_id` from `trusted_height`.
+    fn initialize(chain_id: &str, trusted_height: u64, trusted_hash: B256) -> Bytes {
+        IBlobstream::initializeCall {
+            _admin: Address::repeat_byte(1),
+            _verifier: Address::repeat_byte(2),
+            _trustedHash: trusted_hash,
+            _trustedHeight: trusted_height,
+            _minBatchSize: 7,
+            _chainIdHash: chain_id_hash(&chain_id.parse().unwrap()).into(),
+            _leafContent: LeafContent::DataHash.into(),
+        }
+        .abi_encode()
+        .into()
+    }
+
+    #[test]
+    fn create2_proxy_address_is_chain_independent() {
+        let salt = B256::with_last_byte(1);
+        let implementation = Address::repeat_byte(3);
+        let (mainnet, mainnet_calls) = create2_proxy_calls(
+            DETERMINISTIC_DEPLOYER,
+            salt,
+            implementation,
+            initialize("celestia", 1802142, B256::repeat_byte(4)),
+        );
+        let (testnet, testnet_calls) = create2_proxy_calls(
+            DETERMINISTIC_DEPLOYER,
+            salt,
+            implementation,
+            initialize("mocha-4", 2500000, B256::repeat_byte(5)),
+        );
+
+        assert_eq!(mainnet, testnet);
+        assert_eq!(
+            mainnet,
+            DETERMINISTIC_DEPLOYER.create2_from_code(salt, proxy_init_code(implementation))
+        );
+        // The same proxy is deployed, and only initialized with the trusted state of each chain.
+        assert_eq!(mainnet_calls[0].target, DETERMINISTIC_DEPLOYER);
+        assert_eq!(testnet_calls[0].target, DETERMINISTIC_DEPLOYER);
+        assert_eq!(mainnet_calls[0].callData, testnet_calls[0].callData);
+        assert_eq!(mainnet_calls[1].target, mainnet);
+        assert_ne!(mainnet_calls[1].callData, testnet_calls[1].callData);
+    }
+}
@@ -14,53 +14,245 @@
 
 use alloy::{
     network::{Network, TransactionBuilder},
-    primitives::Address,
+    primitives::{Address, U256},
     providers::{
         fillers::{FillerControlFlow, TxFiller},
         Provider, SendableTx,
     },
-    transports::TransportResult,
+    transports::{RpcError, TransportResult},
 };
+use clap::Args;
 
-#[derive(Clone, Copy, Debug, Default)]
+/// Default cap for the max fee per gas, 1 Ether.
+const DEFAULT_MAX_FEE_PER_GAS: u128 = 0x0de0b6b3a7640000;
+
+/// Limits on the fees of transactions sent through Fireblocks. The fees and gas limit are first
+/// filled by the provider, then adjusted and checked against these limits before submission.
+#[derive(Args, Clone, Copy, Debug)]
+pub(crate) struct FeePolicy {
+    /// Maximum fee per gas in wei. Estimated fees are capped to this value, and sending fails if
+    /// the current gas price exceeds it.
+    #[clap(long, env, default_value_t = DEFAULT_MAX_FEE_PER_GAS)]
+    pub max_fee_per_gas: u128,
+
+    /// Maximum priority fee per gas in wei. Estimated priority fees are capped to this value.
+    #[clap(long, env)]
+    pub max_priority_fee_per_gas: Option<u128>,
+
+    /// Multiplier applied to the estimated gas limit. Must be at least 1.0.
+    #[clap(long, env, default_value_t = 1.0, value_parser = parse_gas_limit_multiplier)]
+    pub gas_limit_multiplier: f64,
+
+    /// Maximum total spend of a transaction in wei, including value. Sending fails if the gas
+    /// limit multiplied by the max fee per gas, plus value, exceeds this.
+    #[clap(long, env)]
+    pub max_tx_cost: Option<u128>,
+}
+
+/// Parses a gas limit multiplier, which must not reduce the estimated gas limit.
+fn parse_gas_limit_multiplier(s: &str) -> Result<f64, String> {
+    let multiplier: f64 = s.parse().map_err(|e| format!("{e}"))?;
+    if !multiplier.is_finite() || multiplier < 1.0 {
+        return Err(format!(
+            "gas limit multiplier must be a finite number of at least 1.0, got {s}"
+        ));
+    }
+    Ok(multiplier)
+}
+
+impl FeePolicy {
+    /// Caps the fees of the transaction. This is idempotent, so can be applied at any point.
+    fn cap_fees<N: Network>(&self, builder: &mut N::TransactionRequest) {
+        if let Some(fee) = builder.max_fee_per_gas() {
+            builder.set_max_fee_per_gas(fee.min(self.max_fee_per_gas));
+        }
+        if let Some(fee) = builder.gas_price() {
+            builder.set_gas_price(fee.min(self.max_fee_per_gas));
+        }
+        if let Some(priority_fee) = builder.max_priority_fee_per_gas() {
+            let cap = self
+                .max_priority_fee_per_gas
+                .unwrap_or(u128::MAX)
+                .min(builder.max_fee_per_gas().unwrap_or(u128::MAX));
+            builder.set_max_priority_fee_per_gas(priority_fee.min(cap));
+        }
+    }
+
+    /// Applies the policy to the transaction with filled gas values, returning an error if the
+    /// transaction would exceed any of the limits.
+    fn apply<N: Network>(
+        &self,
+        builder: &mut N::TransactionRequest,
+        gas_price: u128,
+    ) -> TransportResult<()> {
+        if gas_price > self.max_fee_per_gas {
+            return Err(RpcError::local_usage_str(&format!(
+                "current gas price of {} wei exceeds the max fee per gas of {} wei",
+                gas_price, self.max_fee_per_gas
+            )));
+        }
+
+        if let Some(gas_limit) = builder.gas_limit() {
+            let gas_limit = (gas_limit as f64 * self.gas_limit_multiplier).ceil() as u64;
+            builder.set_gas_limit(gas_limit);
+        }
+        self.cap_fees::<N>(builder);
+
+        if let Some(max_tx_cost) = self.max_tx_cost {
+            let gas_limit = builder.gas_limit().unwrap_or_default();
+            let fee = builder
+                .max_fee_per_gas()
+                .or(builder.gas_price())
+                .unwrap_or_default();
+            let tx_cost =
+                U256::from(gas_limit) * U256::from(fee) + builder.value().unwrap_or_default();
+            if tx_cost > U256::from(max_tx_cost) {
+                return Err(RpcError::local_usage_str(&format!(
+                    "transaction cost of up to {} wei (gas limit {}, max fee per gas {} wei) \
+                     exceeds the max transaction cost of {} wei",
+                    tx_cost, gas_limit, fee, max_tx_cost
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
 pub(crate) struct FireblocksFiller {
     pub sender: Address,
+    pub fee_policy: FeePolicy,
 }
 
 impl<N: Network> TxFiller<N> for FireblocksFiller {
-    type Fillable = ();
+    /// Current gas price, to check against the fee policy.
+    type Fillable = u128;
 
-    fn status(&self, _tx: &<N as Network>::TransactionRequest) -> FillerControlFlow {
-        FillerControlFlow::Finished
+    fn status(&self, tx: &<N as Network>::TransactionRequest) -> FillerControlFlow {
+        // Ready until the gas fields are filled, so that the fee policy is applied in the same
+        // round that the gas filler estimates them.
+        let gas_filled = tx.gas_limit().is_some()
+            && (tx.gas_price().is_some()
+                || (tx.max_fee_per_gas().is_some() && tx.max_priority_fee_per_gas().is_some()));
+        if tx.from() != Some(self.sender) || !gas_filled {
+            FillerControlFlow::Ready
+        } else {
+            FillerControlFlow::Finished
+        }
     }
 
     fn fill_sync(&self, tx: &mut SendableTx<N>) {
         if let Some(builder) = tx.as_mut_builder() {
-            // 1 Ether max tx fee
-            if let Some(fee) = builder.max_fee_per_gas() {
-                builder.set_max_fee_per_gas(core::cmp::min(0x0de0b6b3a7640000, fee));
-            }
+            self.fee_policy.cap_fees::<N>(builder);
 
-            builder.set_from(self.sender.clone());
+            builder.set_from(self.sender);
         }
     }
 
     async fn prepare<P>(
         &self,
-        _provider: &P,
+        provider: &P,
         _tx: &<N as Network>::TransactionRequest,
     ) -> TransportResult<Self::Fillable>
     where
         P: Provider<N>,
     {
-        Ok(())
+        provider.get_gas_price().await
     }
 
     async fn fill(
         &self,
-        _fillable: Self::Fillable,
-        tx: SendableTx<N>,
+        gas_price: Self::Fillable,
+        mut tx: SendableTx<N>,
     ) -> TransportResult<SendableTx<N>> {
+        if let Some(builder) = tx.as_mut_builder() {
+            self.fee_policy.apply::<N>(builder, gas_price)?;
+        }
         Ok(tx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy::{network::Ethereum, rpc::types::TransactionRequest};
+
+    use super::*;
+
+    fn policy() -> FeePolicy {
+        FeePolicy {
+            max_fee_per_gas: 100,
+            max_priority_fee_per_gas: Some(10),
+            gas_limit_multiplier: 1.0,
+            max_tx_cost: None,
+        }
+    }
+
+    fn eip1559_tx(gas_limit: u64, max_fee: u128, priority_fee: u128) -> TransactionRequest {
+        TransactionRequest::default()
+            .with_gas_limit(gas_limit)
+            .with_max_fee_per_gas(max_fee)
+            .with_max_priority_fee_per_gas(priority_fee)
+    }
+
+    #[test]
+    fn caps_fees() {
+        let mut tx = eip1559_tx(21_000, 500, 50);
+        policy().apply::<Ethereum>(&mut tx, 80).unwrap();
+        assert_eq!(tx.max_fee_per_gas, Some(100));
+        assert_eq!(tx.max_priority_fee_per_gas, Some(10));
+
+        // Fees under the caps are unchanged.
+        let mut tx = eip1559_tx(21_000, 90, 5);
+        policy().apply::<Ethereum>(&mut tx, 80).unwrap();
+        assert_eq!(tx.max_fee_per_gas, Some(90));
+        assert_eq!(tx.max_priority_fee_per_gas, Some(5));
+
+        let mut tx = TransactionRequest::default().with_gas_price(500);
+        policy().cap_fees::<Ethereum>(&mut tx);
+        assert_eq!(tx.gas_price, Some(100));
+    }
+
+    #[test]
+    fn rejects_gas_price_above_cap() {
+        let mut tx = eip1559_tx(21_000, 500, 50);
+        assert!(policy().apply::<Ethereum>(&mut tx, 101).is_err());
+    }
+
+    #[test]
+    fn applies_gas_limit_multiplier() {
+        let policy = FeePolicy {
+            gas_limit_multiplier: 1.5,
+            ..policy()
+        };
+        let mut tx = eip1559_tx(21_001, 100, 10);
+        policy.apply::<Ethereum>(&mut tx, 80).unwrap();
+        assert_eq!(tx.gas, Some(31_502));
+    }
+
+    #[test]
+    fn rejects_max_tx_cost() {
+        let policy = FeePolicy {
+            gas_limit_multiplier: 2.0,
+            max_tx_cost: Some(4_200_000),
+            ..policy()
+        };
+        // 2 * 21_000 gas at the capped 100 wei is exactly the max cost.
+        let mut tx = eip1559_tx(21_000, 500, 10);
+        policy.apply::<Ethereum>(&mut tx, 80).unwrap();
+
+        // Value is included in the cost.
+        let mut tx = eip1559_tx(21_000, 500, 10).with_value(U256::from(1));
+        assert!(policy.apply::<Ethereum>(&mut tx, 80).is_err());
+    }
+
+    #[test]
+    fn gas_limit_multiplier_bounds() {
+        assert_eq!(parse_gas_limit_multiplier("1.0"), Ok(1.0));
+        assert_eq!(parse_gas_limit_multiplier("1.25"), Ok(1.25));
+        assert!(parse_gas_limit_multiplier("0.99").is_err());
+        assert!(parse_gas_limit_multiplier("NaN").is_err());
+        assert!(parse_gas_limit_multiplier("inf").is_err());
+        assert!(parse_gas_limit_multiplier("abc").is_err());
+    }
+}
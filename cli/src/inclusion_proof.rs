@@ -0,0 +1,148 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Command to generate the `verifyAttestation` or `verifyHeaderHash` arguments for a height
+//! committed to the contract.
+
+use std::sync::Arc;
+
+use alloy::{primitives::Address, providers::ProviderBuilder};
+use blobstream0_core::{data_root_inclusion_proof, header_hash_inclusion_proof, tendermint_client};
+use blobstream0_primitives::{
+    proto::ProtoVersion,
+    IBlobstream::{self, BinaryMerkleProof},
+};
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub(crate) struct InclusionProofArgs {
+    /// Height of the block to prove the data root of
+    #[clap(long)]
+    height: u64,
+
+    /// The Tendermint RPC URL
+    #[clap(long, env)]
+    tendermint_rpc: String,
+
+    /// CometBFT protobuf version of the Tendermint chain: 0.34, 0.37 or 0.38
+    #[clap(long, env, default_value_t)]
+    proto_version: ProtoVersion,
+
+    /// The Ethereum RPC URL
+    #[clap(long, env)]
+    eth_rpc: String,
+
+    /// The deployed contract on Ethereum to reference
+    #[clap(long, env)]
+    eth_address: Address,
+
+    /// Ethereum block to start searching for data commitment events from, such as the block the
+    /// contract was deployed in. Set from the deploy block of the manifest with `--manifest`.
+    #[clap(long, env)]
+    from_block: u64,
+
+    /// Prove the header hash of the height against the header hash root, instead of the data root
+    /// against the data commitment
+    #[clap(long)]
+    header_hash: bool,
+
+    /// Also check the proof by calling `verifyAttestation` (or `verifyHeaderHash`) on the contract
+    #[clap(long)]
+    verify: bool,
+}
+
+impl InclusionProofArgs {
+    pub(crate) async fn run(self) -> anyhow::Result<()> {
+        let client = Arc::new(tendermint_client(&self.tendermint_rpc, self.proto_version)?);
+        let provider = ProviderBuilder::new().connect_http(self.eth_rpc.parse()?);
+        let contract = IBlobstream::new(self.eth_address, provider);
+
+        if self.header_hash {
+            let inclusion =
+                header_hash_inclusion_proof(client, &contract, self.height, self.from_block)
+                    .await?;
+
+            let mut output = serde_json::json!({
+                "proofNonce": inclusion.commitment.proof_nonce.to_string(),
+                "startBlock": inclusion.commitment.start_block,
+                "endBlock": inclusion.commitment.end_block,
+                "headerHash": inclusion.header_hash.to_string(),
+                "proof": proof_json(&inclusion.proof),
+            });
+
+            let mut verified = true;
+            if self.verify {
+                verified = contract
+                    .verifyHeaderHash(
+                        inclusion.commitment.proof_nonce,
+                        inclusion.header_hash,
+                        inclusion.proof,
+                    )
+                    .call()
+                    .await?;
+                output["verified"] = verified.into();
+            }
+
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            anyhow::ensure!(verified, "verifyHeaderHash returned false for the proof");
+            return Ok(());
+        }
+
+        let inclusion =
+            data_root_inclusion_proof(client, &contract, self.height, self.from_block).await?;
+
+        let mut output = serde_json::json!({
+            "proofNonce": inclusion.commitment.proof_nonce.to_string(),
+            "startBlock": inclusion.commitment.start_block,
+            "endBlock": inclusion.commitment.end_block,
+            "dataCommitment": inclusion.commitment.data_commitment.to_string(),
+            "tuple": {
+                "height": inclusion.tuple.height.to_string(),
+                "dataRoot": inclusion.tuple.dataRoot.to_string(),
+            },
+            "proof": proof_json(&inclusion.proof),
+        });
+
+        let mut verified = true;
+        if self.verify {
+            verified = contract
+                .verifyAttestation(
+                    inclusion.commitment.proof_nonce,
+                    inclusion.tuple,
+                    inclusion.proof,
+                )
+                .call()
+                .await?;
+            output["verified"] = verified.into();
+        }
+
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        anyhow::ensure!(verified, "verifyAttestation returned false for the proof");
+
+        Ok(())
+    }
+}
+
+fn proof_json(proof: &BinaryMerkleProof) -> serde_json::Value {
+    serde_json::json!({
+        "sideNodes": proof
+            .sideNodes
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        "key": proof.key.to_string(),
+        "numLeaves": proof.numLeaves.to_string(),
+    })
+}
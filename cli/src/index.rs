@@ -0,0 +1,148 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Commands to maintain and query the local index of data commitments stored by the contract.
+
+use std::{path::PathBuf, time::Duration};
+
+use alloy::{primitives::Address, providers::ProviderBuilder};
+use blobstream0_core::{IndexedCommitment, Indexer};
+use blobstream0_primitives::IBlobstream;
+use clap::{Args, Subcommand};
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum IndexCommand {
+    /// Index the data commitment events of the contract into the local database.
+    Sync(SyncArgs),
+    /// Query the commitments in the local database.
+    Query(QueryArgs),
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct SyncArgs {
+    /// Path to the index database, created if it does not exist
+    #[clap(long, env)]
+    index_db: PathBuf,
+
+    /// The Ethereum RPC URL
+    #[clap(long, env)]
+    eth_rpc: String,
+
+    /// The deployed contract on Ethereum to reference
+    #[clap(long, env)]
+    eth_address: Address,
+
+    /// Ethereum block to start indexing from when the database is empty, such as the block the
+    /// contract was deployed in
+    #[clap(long, env, default_value_t = 0)]
+    from_block: u64,
+
+    /// Number of confirmations before a block is indexed
+    #[clap(long, env, default_value_t = 0)]
+    confirmations: u64,
+
+    /// Keep indexing new blocks, polling at this interval in seconds
+    #[clap(long)]
+    follow: Option<u64>,
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct QueryArgs {
+    /// Path to the index database
+    #[clap(long, env)]
+    index_db: PathBuf,
+
+    #[clap(flatten)]
+    query: Query,
+}
+
+/// Commitments to query, only one of which can be set.
+#[derive(Args, Debug)]
+#[group(required = true, multiple = false)]
+struct Query {
+    /// Find the commitment including the data root of this height
+    #[clap(long)]
+    height: Option<u64>,
+
+    /// Find the commitment stored at this proof nonce
+    #[clap(long)]
+    nonce: Option<u64>,
+
+    /// Find all commitments including any height in this range, as `start..=end`
+    #[clap(long, value_parser = parse_height_range)]
+    heights: Option<(u64, u64)>,
+}
+
+impl IndexCommand {
+    pub(crate) async fn run(self) -> anyhow::Result<()> {
+        match self {
+            IndexCommand::Sync(args) => {
+                let indexer = Indexer::open(&args.index_db, args.from_block)?
+                    .with_confirmations(args.confirmations);
+                let provider = ProviderBuilder::new().connect_http(args.eth_rpc.parse()?);
+                let contract = IBlobstream::new(args.eth_address, provider);
+
+                match args.follow {
+                    Some(interval) => {
+                        indexer
+                            .run(&contract, Duration::from_secs(interval))
+                            .await?
+                    }
+                    None => {
+                        let indexed = indexer.sync(&contract).await?;
+                        println!("indexed {} new commitments", indexed);
+                    }
+                }
+            }
+            IndexCommand::Query(args) => {
+                let indexer = Indexer::open(&args.index_db, 0)?;
+                let commitments = match (args.query.height, args.query.nonce, args.query.heights) {
+                    (Some(height), _, _) => {
+                        indexer.commitment_for_height(height)?.into_iter().collect()
+                    }
+                    (_, Some(nonce), _) => indexer.commitment(nonce)?.into_iter().collect(),
+                    (_, _, Some((start, end))) => indexer.commitments_between(start, end)?,
+                    _ => Vec::new(),
+                };
+                let output: Vec<_> = commitments.iter().map(commitment_json).collect();
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_height_range(value: &str) -> Result<(u64, u64), String> {
+    let (start, end) = value
+        .split_once("..=")
+        .ok_or("expected a range as start..=end")?;
+    let parse = |height: &str| height.parse::<u64>().map_err(|e| e.to_string());
+    Ok((parse(start)?, parse(end)?))
+}
+
+fn commitment_json(indexed: &IndexedCommitment) -> serde_json::Value {
+    serde_json::json!({
+        "proofNonce": indexed.commitment.proof_nonce.to_string(),
+        "startBlock": indexed.commitment.start_block,
+        "endBlock": indexed.commitment.end_block,
+        "dataCommitment": indexed.commitment.data_commitment.to_string(),
+        "headerHash": indexed.header_hash.to_string(),
+        "validatorBitmap": format!("{:#x}", indexed.validator_bitmap),
+        "startBlockTime": indexed.trusted_header_time,
+        "endBlockTime": indexed.header_time,
+        "ethBlockNumber": indexed.eth_block_number,
+        "ethBlockHash": indexed.eth_block_hash.to_string(),
+        "transactionHash": indexed.transaction_hash.to_string(),
+    })
+}
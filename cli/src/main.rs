@@ -12,25 +12,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use alloy::{
-    hex::FromHex,
-    primitives::{hex, Address, FixedBytes},
-    providers::ProviderBuilder,
-};
-use alloy_sol_types::{sol, SolCall};
-use blobstream0_core::prove_block_range;
-use blobstream0_primitives::IBlobstream;
-use clap::Parser;
+use alloy::primitives::hex;
+use alloy_sol_types::sol;
+use blobstream0_core::{execute_block_range, prove_block_range, tendermint_client};
+use blobstream0_primitives::{proto::ProtoVersion, LeafContent, ProofConfig};
+use clap::{Args, Parser, Subcommand};
 use dotenv::dotenv;
 use std::{path::PathBuf, sync::Arc};
-use tendermint_rpc::HttpClient;
 use tokio::fs;
 use tracing_subscriber::fmt::format;
 use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
 
+mod admin;
+mod config;
+mod deploy;
 #[cfg(feature = "fireblocks")]
 pub(crate) mod fireblocks;
+mod inclusion_proof;
+mod index;
+mod manifest;
+mod receipt;
+mod safe;
+mod serve;
 mod service;
+mod signer;
+mod status;
+mod storage_layout;
 
 sol!(
     #[sol(rpc)]
@@ -54,42 +61,60 @@ const CONTROL_ID: [u8; 32] =
 const BN254_CONTROL_ID: [u8; 32] =
     hex!("05a022e1db38457fb510bc347b30eb8f8cf3eda95587653d0eac19e1f10d164e");
 
-#[cfg(feature = "fireblocks")]
-macro_rules! setup_provider {
-    ($cli:ident) => {{
-        let fireblocks_address: Address = $cli.fireblocks_address.parse()?;
-        let provider = ProviderBuilder::new()
-            .fetch_chain_id()
-            .filler(crate::fireblocks::FireblocksFiller {
-                sender: fireblocks_address,
-            })
-            .connect_http($cli.eth_rpc.parse()?);
-        (provider, fireblocks_address)
-    }};
-}
-
-#[cfg(not(feature = "fireblocks"))]
-macro_rules! setup_provider {
-    ($cli:ident) => {{
-        let signer: alloy::signers::local::PrivateKeySigner = $cli.private_key_hex.parse()?;
-        let signer_address = signer.address();
-        let provider = ProviderBuilder::new()
-            .wallet(alloy::network::EthereumWallet::from(signer))
-            .connect_http($cli.eth_rpc.parse()?);
-        (provider, signer_address)
-    }};
-}
-
-pub(crate) use setup_provider;
-
 #[derive(Parser, Debug)]
 #[command(name = "blobstream0-cli")]
 #[command(bin_name = "blobstream0-cli")]
+struct Cli {
+    #[command(flatten)]
+    config: config::ConfigArgs,
+
+    #[command(flatten)]
+    manifest: manifest::ManifestArgs,
+
+    #[command(subcommand)]
+    command: BlobstreamCli,
+}
+
+#[derive(Subcommand, Debug)]
 enum BlobstreamCli {
     Service(service::ServiceArgs),
     ProveRange(ProveRangeArgs),
-    Deploy(DeployArgs),
-    Upgrade(UpgradeArgs),
+    ExecuteRange(ExecuteRangeArgs),
+    VerifyReceipt(receipt::VerifyReceiptArgs),
+    PostReceipt(receipt::PostReceiptArgs),
+    InclusionProof(inclusion_proof::InclusionProofArgs),
+    Status(status::StatusArgs),
+    #[command(subcommand)]
+    Index(index::IndexCommand),
+    Serve(serve::ServeArgs),
+    Deploy(deploy::DeployArgs),
+    Upgrade(deploy::UpgradeArgs),
+    #[command(subcommand)]
+    Admin(admin::AdminCommand),
+    #[command(subcommand)]
+    Config(config::ConfigCommand),
+}
+
+/// Options selecting how the blocks of the Tendermint chain are decoded and committed to.
+#[derive(Args, Debug, Clone, Copy)]
+pub(crate) struct ProofConfigArgs {
+    /// Header field committed for each block: data-hash, app-hash or header-hash. Must match the
+    /// leaf content the contract was deployed with.
+    #[clap(long, env, default_value_t)]
+    leaf_content: LeafContent,
+
+    /// CometBFT protobuf version of the Tendermint chain: 0.34, 0.37 or 0.38
+    #[clap(long, env, default_value_t)]
+    proto_version: ProtoVersion,
+}
+
+impl From<ProofConfigArgs> for ProofConfig {
+    fn from(args: ProofConfigArgs) -> Self {
+        Self {
+            proto_version: args.proto_version,
+            leaf_content: args.leaf_content,
+        }
+    }
 }
 
 #[derive(Parser, Debug)]
@@ -110,170 +135,116 @@ struct ProveRangeArgs {
     /// Output file path to write serialized receipt to
     #[clap(long, short)]
     out: PathBuf,
-}
 
-#[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
-struct DeployArgs {
-    /// The Ethereum RPC URL
-    #[clap(long, env)]
-    eth_rpc: String,
+    /// Format to write the receipt in
+    #[clap(long, value_enum, default_value_t)]
+    format: receipt::ReceiptFormat,
 
-    #[cfg(feature = "fireblocks")]
-    /// Fireblocks signer address.
-    #[clap(long, env)]
-    fireblocks_address: String,
-
-    #[cfg(not(feature = "fireblocks"))]
-    /// Hex encoded private key to use for deploying.
-    #[clap(long, env)]
-    private_key_hex: String,
-
-    /// Hex encoded address of admin for upgrades. Will default to the private key address.
-    #[clap(long, env)]
-    admin_address: Option<String>,
-
-    /// Address of risc0 verifier to use (either mock or groth16)
-    #[clap(long, env)]
-    verifier_address: Option<String>,
-
-    /// Trusted height for contract
-    #[clap(long, env)]
-    tm_height: u64,
-
-    /// Trusted block hash for contract
-    #[clap(long, env)]
-    tm_block_hash: String,
-
-    /// Minimum batch size for head updates. The batch size must be larger than this value.
-    #[clap(long, env)]
-    min_batch_size: u64,
-
-    /// If deploying verifier, will it deploy the mock verifier
-    #[clap(long)]
-    dev: bool,
+    #[clap(flatten)]
+    proof_config: ProofConfigArgs,
 }
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
-struct UpgradeArgs {
-    /// The Ethereum RPC URL
-    #[clap(long, env)]
-    eth_rpc: String,
+struct ExecuteRangeArgs {
+    /// The start height
+    #[clap(long)]
+    start: u64,
 
-    #[cfg(feature = "fireblocks")]
-    /// Fireblocks signer address.
-    #[clap(long, env)]
-    fireblocks_address: String,
+    /// The end height of the batch
+    #[clap(long)]
+    end: u64,
 
-    #[cfg(not(feature = "fireblocks"))]
-    /// Hex encoded private key to use for deploying.
+    /// The Tendermint RPC URL
     #[clap(long, env)]
-    private_key_hex: String,
+    tendermint_rpc: String,
 
-    /// Hex encoded address of admin for upgrades. Will default to the private key address.
-    #[clap(long, env)]
-    proxy_address: String,
+    #[clap(flatten)]
+    proof_config: ProofConfigArgs,
 }
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
+fn main() -> anyhow::Result<()> {
     dotenv().ok();
+    // Values from the config file are only used where not already set by the environment. The
+    // environment is modified, so this must happen before the runtime starts its worker threads.
+    let resolved_config = config::load_from_args()?;
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(run(resolved_config))
+}
 
+async fn run(resolved_config: config::ResolvedConfig) -> anyhow::Result<()> {
     tracing_subscriber::fmt()
         .event_format(format().compact())
         .with_span_events(FmtSpan::CLOSE)
         .with_env_filter(EnvFilter::from_default_env())
         .init();
 
-    match BlobstreamCli::parse() {
+    let cli = Cli::parse();
+    let manifest = cli.manifest.manifest;
+    match cli.command {
         BlobstreamCli::ProveRange(range) => {
             let ProveRangeArgs {
                 start,
                 end,
                 tendermint_rpc,
                 out,
+                format,
+                proof_config,
             } = range;
 
-            let client = Arc::new(HttpClient::new(tendermint_rpc.as_str())?);
+            let client = Arc::new(tendermint_client(
+                &tendermint_rpc,
+                proof_config.proto_version,
+            )?);
 
-            let receipt = prove_block_range(client, start..end).await?;
+            let receipt = prove_block_range(client, start..end, proof_config.into()).await?;
 
-            fs::write(out, bincode::serialize(&receipt)?).await?;
+            fs::write(out, format.encode(&receipt)?).await?;
         }
-        BlobstreamCli::Deploy(deploy) => {
-            let (provider, signer_address) = setup_provider!(deploy);
-            let admin_address: Address = if let Some(address) = deploy.admin_address {
-                address.parse()?
-            } else {
-                signer_address
-            };
-
-            let verifier_address: Address = if let Some(address) = deploy.verifier_address {
-                address.parse()?
-            } else {
-                let deployed_address = if deploy.dev {
-                    tracing::debug!(target: "blobstream0::cli", "Deploying mock verifier");
-                    MockVerifier::deploy(&provider, [0, 0, 0, 0].into())
-                        .await?
-                        .address()
-                        .clone()
-                } else {
-                    tracing::debug!(target: "blobstream0::cli", "Deploying groth16 verifier");
-                    RiscZeroGroth16Verifier::deploy(
-                        &provider,
-                        CONTROL_ID.into(),
-                        BN254_CONTROL_ID.into(),
-                    )
-                    .await?
-                    .address()
-                    .clone()
-                };
-                println!("deployed verifier to address: {}", deployed_address);
-                deployed_address
-            };
-
-            // Deploy the contract.
-            let implementation = IBlobstream::deploy(&provider).await?;
-            tracing::debug!(target: "blobstream0::cli", "Deployed implementation contract to {}", implementation.address());
-
-            let proxy = ERC1967Proxy::deploy(
-                &provider,
-                implementation.address().clone(),
-                IBlobstream::initializeCall {
-                    _admin: admin_address,
-                    _verifier: verifier_address,
-                    _trustedHash: FixedBytes::<32>::from_hex(deploy.tm_block_hash)?,
-                    _trustedHeight: deploy.tm_height,
-                    _minBatchSize: deploy.min_batch_size,
-                }
-                .abi_encode()
-                .into(),
-            )
-            .await?;
-            tracing::debug!(target: "blobstream0::cli", "Deployed proxy contract");
-
-            println!("deployed contract to address: {}", proxy.address());
-        }
-        BlobstreamCli::Upgrade(upgrade) => {
-            let (provider, _) = setup_provider!(upgrade);
-
-            let proxy_address: Address = upgrade.proxy_address.parse()?;
-            println!("proxy address: {}", proxy_address);
-
-            let implementation = IBlobstream::deploy(&provider).await?;
-            tracing::debug!(target: "blobstream0::cli", "Deployed new implementation contract to {}", implementation.address());
-
-            IBlobstream::new(proxy_address, provider.clone())
-                .upgradeToAndCall(implementation.address().clone(), Default::default())
-                .send()
-                .await?
-                .watch()
-                .await?;
-            tracing::debug!(target: "blobstream0::cli", "Upgraded proxy contract to new implementation");
-            println!("Upgraded proxy contract to {}", implementation.address());
+        BlobstreamCli::ExecuteRange(range) => {
+            let ExecuteRangeArgs {
+                start,
+                end,
+                tendermint_rpc,
+                proof_config,
+            } = range;
+
+            let client = Arc::new(tendermint_client(
+                &tendermint_rpc,
+                proof_config.proto_version,
+            )?);
+
+            let report = execute_block_range(client, start..end, proof_config.into()).await?;
+
+            let output = serde_json::json!({
+                "totalCycles": report.total_cycles,
+                "userCycles": report.user_cycles,
+                "segments": report.segments,
+                "inputBytes": report.input_bytes,
+                "phaseCycles": report.phase_cycles.map(|phases| serde_json::json!({
+                    "decode": phases.decode,
+                    "bitmap": phases.bitmap,
+                    "merkleRoot": phases.merkle_root,
+                    "lightClientVerify": phases.light_client_verify,
+                })),
+                "commitment": receipt::commitment_json(&report.commitment),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
         }
+        BlobstreamCli::Deploy(args) => args.run(manifest.as_deref()).await?,
+        BlobstreamCli::Upgrade(args) => args.run(manifest.as_deref()).await?,
+        BlobstreamCli::VerifyReceipt(args) => args.run().await?,
+        BlobstreamCli::PostReceipt(args) => args.run().await?,
+        BlobstreamCli::InclusionProof(args) => args.run().await?,
+        BlobstreamCli::Status(args) => args.run().await?,
+        BlobstreamCli::Index(command) => command.run().await?,
+        BlobstreamCli::Serve(args) => args.start().await?,
         BlobstreamCli::Service(service) => service.start().await?,
+        BlobstreamCli::Admin(command) => command.run().await?,
+        BlobstreamCli::Config(command) => command.run(&resolved_config)?,
     }
 
     Ok(())
@@ -0,0 +1,159 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! JSON manifest of a deployment, written by `deploy` and updated by `upgrade`.
+//!
+//! Like the configuration file, the manifest selected with `--manifest` provides defaults for the
+//! environment variables the CLI arguments are read from, so that commands operating on the
+//! deployment do not need the addresses repeated.
+
+use std::{
+    env,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use alloy::primitives::{Address, TxHash, B256};
+use anyhow::Context;
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::{status::VerifierKind, storage_layout::StorageLayout};
+
+/// Environment variable to read the manifest path from, if `--manifest` is not passed.
+const MANIFEST_ENV: &str = "BLOBSTREAM0_MANIFEST";
+
+/// Path of the deployment manifest. Also read before the rest of the command line is parsed, to
+/// apply the manifest values as environment variable defaults.
+#[derive(Args, Debug)]
+pub(crate) struct ManifestArgs {
+    /// Path to the JSON deployment manifest. Written by `deploy`, updated by `upgrade`, and used
+    /// for the contract address and chain ID of other commands.
+    #[clap(long, global = true, env = MANIFEST_ENV)]
+    pub manifest: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DeploymentManifest {
+    pub chain_id: u64,
+    pub proxy_address: Address,
+    pub implementation_address: Address,
+    pub verifier_address: Address,
+    pub verifier_kind: VerifierKind,
+    pub image_id: B256,
+    pub trusted_height: u64,
+    pub trusted_hash: B256,
+    /// Tendermint chain ID the contract accepts proofs for, not recorded by older deployments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tm_chain_id: Option<String>,
+    /// Header field committed for each block, not recorded by older deployments which all commit
+    /// to the data hash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaf_content: Option<String>,
+    pub min_batch_size: u64,
+    pub admin: Address,
+    /// Ethereum block the proxy was deployed in, to start searching for events from.
+    pub deploy_block: u64,
+    /// Salt the contracts were deployed with through the deterministic deployment proxy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub create2_salt: Option<B256>,
+    /// Storage layout of the deployed implementation, which upgrades are checked against. Not
+    /// recorded by older deployments, which are upgraded with `--previous-artifact` instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage_layout: Option<StorageLayout>,
+    pub transactions: DeploymentTransactions,
+}
+
+/// Hashes of the transactions of a deployment.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DeploymentTransactions {
+    /// Deployment of the verifier, if it was deployed with the contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifier: Option<TxHash>,
+    pub implementation: TxHash,
+    pub proxy: TxHash,
+    /// Upgrades of the proxy, in the order they were made.
+    #[serde(default)]
+    pub upgrades: Vec<UpgradeTransactions>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UpgradeTransactions {
+    pub implementation_address: Address,
+    /// Deployment of the new implementation.
+    pub implementation: TxHash,
+    /// Call to `upgradeToAndCall` on the proxy.
+    pub upgrade: TxHash,
+}
+
+impl DeploymentManifest {
+    pub(crate) fn read(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read manifest {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse manifest {}", path.display()))
+    }
+
+    /// Writes a new manifest, failing if the file already exists to not lose a previous
+    /// deployment.
+    pub(crate) fn create(&self, path: &Path) -> anyhow::Result<()> {
+        let mut file = std::fs::File::create_new(path)
+            .with_context(|| format!("failed to create manifest {}", path.display()))?;
+        file.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Replaces an existing manifest, through a temporary file so that it is never left partially
+    /// written.
+    pub(crate) fn update(&self, path: &Path) -> anyhow::Result<()> {
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp_path, path)
+            .with_context(|| format!("failed to update manifest {}", path.display()))
+    }
+
+    /// Environment variables the manifest provides values for.
+    fn env_values(&self) -> Vec<(&'static str, String)> {
+        let mut values = vec![
+            ("ETH_CHAIN_ID", self.chain_id.to_string()),
+            ("ETH_ADDRESS", self.proxy_address.to_string()),
+            ("PROXY_ADDRESS", self.proxy_address.to_string()),
+            ("FROM_BLOCK", self.deploy_block.to_string()),
+        ];
+        if let Some(leaf_content) = &self.leaf_content {
+            values.push(("LEAF_CONTENT", leaf_content.clone()));
+        }
+        values
+    }
+}
+
+/// Path of the manifest selected by `--manifest` or `BLOBSTREAM0_MANIFEST`.
+pub(crate) fn path_from_args(args: &[String]) -> Option<PathBuf> {
+    crate::config::flag_value(args, "--manifest")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os(MANIFEST_ENV).map(PathBuf::from))
+}
+
+/// Values of the manifest at `path` for the environment variables it provides defaults for.
+/// There are none if the manifest does not exist yet, as when it is about to be written by
+/// `deploy`.
+pub(crate) fn env_values(path: Option<&Path>) -> anyhow::Result<Vec<(&'static str, String)>> {
+    match path {
+        Some(path) if path.exists() => Ok(DeploymentManifest::read(path)?.env_values()),
+        _ => Ok(Vec::new()),
+    }
+}
@@ -0,0 +1,313 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Commands for receipts written by `prove-range`.
+
+use std::path::{Path, PathBuf};
+
+use alloy::{
+    eips::BlockNumberOrTag,
+    hex,
+    primitives::{Address, B256, U256},
+    providers::{Provider, ProviderBuilder},
+};
+use alloy_sol_types::{SolCall, SolValue};
+use anyhow::Context;
+use blobstream0_core::{
+    encode_seal, light_client_image_id, post_batch, verify_range_receipt, Digest, Receipt,
+};
+use blobstream0_primitives::{
+    IBlobstream::{self, IBlobstreamInstance},
+    RangeCommitment,
+};
+use clap::{Parser, ValueEnum};
+use tokio::fs;
+
+use crate::{
+    safe::{self, SafeExportArgs, SafeTransaction},
+    signer::{ensure_chain_id, SignerArgs},
+};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub(crate) struct VerifyReceiptArgs {
+    /// Path to the serialized receipt written by `prove-range`
+    #[clap(long, short)]
+    receipt: PathBuf,
+
+    /// Hex encoded image ID to verify the receipt against. Defaults to the light client guest.
+    #[clap(long)]
+    image_id: Option<B256>,
+
+    /// The Ethereum RPC URL, used to check the trusted hash against the contract
+    #[clap(long, env)]
+    eth_rpc: Option<String>,
+
+    /// The deployed contract on Ethereum to check the trusted hash against
+    #[clap(long, env, requires = "eth_rpc")]
+    eth_address: Option<Address>,
+}
+
+impl VerifyReceiptArgs {
+    pub(crate) async fn run(self) -> anyhow::Result<()> {
+        let receipt = read_receipt(&self.receipt).await?;
+        let image_id = match self.image_id {
+            Some(image_id) => Digest::from(image_id.0),
+            None => light_client_image_id()?,
+        };
+        let commitment = verify_range_receipt(&receipt, image_id)?;
+
+        let mut output = commitment_json(&commitment);
+        output["imageId"] = format!("0x{}", image_id).into();
+
+        let mut trusted_hash_matches = true;
+        if let (Some(eth_rpc), Some(eth_address)) = (self.eth_rpc, self.eth_address) {
+            let provider = ProviderBuilder::new().connect_http(eth_rpc.parse()?);
+            let latest_block_hash = IBlobstream::new(eth_address, provider)
+                .latestBlockHash()
+                .call()
+                .await?;
+            trusted_hash_matches = latest_block_hash == commitment.trustedHeaderHash;
+            output["contractLatestBlockHash"] = latest_block_hash.to_string().into();
+            output["trustedHashMatchesContract"] = trusted_hash_matches.into();
+        }
+
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        anyhow::ensure!(
+            trusted_hash_matches,
+            "trusted header hash of the receipt does not match the latest block hash of the contract"
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub(crate) struct PostReceiptArgs {
+    /// Path to the serialized receipt written by `prove-range`
+    #[clap(long, short)]
+    receipt: PathBuf,
+
+    /// The Ethereum RPC URL
+    #[clap(long, env)]
+    eth_rpc: String,
+
+    /// Expected chain ID of the Ethereum RPC, checked before sending any transactions
+    #[clap(long, env)]
+    eth_chain_id: Option<u64>,
+
+    /// The deployed contract on Ethereum to post the receipt to
+    #[clap(long, env)]
+    eth_address: Address,
+
+    #[clap(flatten)]
+    signer: SignerArgs,
+
+    #[clap(flatten)]
+    safe: SafeExportArgs,
+}
+
+impl PostReceiptArgs {
+    pub(crate) async fn run(self) -> anyhow::Result<()> {
+        let receipt = read_receipt(&self.receipt).await?;
+
+        if let Some((path, safe_address)) = self.safe.target() {
+            let provider = ProviderBuilder::new().connect_http(self.eth_rpc.parse()?);
+            ensure_chain_id(&provider, self.eth_chain_id).await?;
+            let contract = IBlobstream::new(self.eth_address, &provider);
+            let (commitment, latest_height) = check_receipt(&contract, &receipt).await?;
+
+            let data = IBlobstream::updateRangeCall {
+                _commitBytes: receipt.journal.bytes.clone().into(),
+                _seal: encode_seal(&receipt)?.into(),
+            }
+            .abi_encode();
+            safe::write_batch(
+                path,
+                provider.get_chain_id().await?,
+                safe_address,
+                "Blobstream0 updateRange",
+                &format!(
+                    "Update range to heights {}..={}",
+                    latest_height + 1,
+                    commitment.newHeight
+                ),
+                &[SafeTransaction {
+                    to: self.eth_address,
+                    value: U256::ZERO,
+                    data: data.into(),
+                }],
+            )?;
+            return Ok(());
+        }
+
+        let (provider, _) = self
+            .signer
+            .connect(&self.eth_rpc, self.eth_chain_id)
+            .await?;
+        let contract = IBlobstream::new(self.eth_address, provider);
+        let (commitment, latest_height) = check_receipt(&contract, &receipt).await?;
+
+        post_batch(&contract, &receipt).await?;
+        println!(
+            "posted receipt for heights {}..={} to {}",
+            latest_height + 1,
+            commitment.newHeight,
+            self.eth_address
+        );
+
+        Ok(())
+    }
+}
+
+/// Checks the receipt against the current contract state, to fail before sending a transaction
+/// that would revert. Returns the commitment of the receipt and the latest height of the contract.
+async fn check_receipt<P: Provider>(
+    contract: &IBlobstreamInstance<P>,
+    receipt: &Receipt,
+) -> anyhow::Result<(RangeCommitment, u64)> {
+    let (image_id, block_hash, latest_height, min_batch_size, chain_id_hash) = tokio::try_join!(
+        async { contract.imageId().call().await },
+        async { contract.latestBlockHash().call().await },
+        async { contract.latestHeight().call().await },
+        async { contract.minBatchSize().call().await },
+        async { contract.chainIdHash().call().await },
+    )?;
+
+    let commitment = verify_range_receipt(receipt, Digest::from(image_id.0))
+        .context("receipt is not valid for the image ID of the contract")?;
+    anyhow::ensure!(
+        commitment.trustedHeaderHash == block_hash,
+        "trusted header hash of the receipt {} does not match the latest block hash of the \
+         contract {}",
+        commitment.trustedHeaderHash,
+        block_hash
+    );
+    anyhow::ensure!(
+        commitment.trustedHeight == latest_height,
+        "trusted height of the receipt {} does not match the latest height of the contract {}",
+        commitment.trustedHeight,
+        latest_height
+    );
+    anyhow::ensure!(
+        commitment.chainIdHash == chain_id_hash,
+        "chain ID hash of the receipt {} does not match the chain ID hash of the contract {}",
+        commitment.chainIdHash,
+        chain_id_hash
+    );
+    let leaf_content = contract.leafContent().call().await?;
+    anyhow::ensure!(
+        commitment.leafContent == leaf_content,
+        "leaf content of the receipt {} does not match the leaf content of the contract {}",
+        commitment.leafContent,
+        leaf_content
+    );
+    anyhow::ensure!(
+        commitment.newHeight > latest_height + min_batch_size,
+        "receipt height {} must be greater than the latest height {} plus the minimum batch \
+         size {}",
+        commitment.newHeight,
+        latest_height,
+        min_batch_size
+    );
+
+    // The contract checks the trusting period against the time of the block the update is
+    // included in, so a receipt close to expiring can still revert.
+    let trusting_period = contract.TRUSTING_PERIOD().call().await?;
+    let latest_block = contract
+        .provider()
+        .get_block_by_number(BlockNumberOrTag::Latest)
+        .await?
+        .context("latest Ethereum block not found")?;
+    anyhow::ensure!(
+        latest_block.header.timestamp <= commitment.trustedHeaderTime + trusting_period,
+        "trusted header of the receipt at time {} is older than the trusting period of {}s, the \
+         trusted state of the contract must be reset with `admin set-trusted-state`",
+        commitment.trustedHeaderTime,
+        trusting_period
+    );
+    Ok((commitment, latest_height))
+}
+
+pub(crate) async fn read_receipt(path: &Path) -> anyhow::Result<Receipt> {
+    let bytes = fs::read(path)
+        .await
+        .with_context(|| format!("failed to read receipt {}", path.display()))?;
+    // Receipts written with `--format json` are JSON objects, which bincode never starts with.
+    if bytes.first() == Some(&b'{') {
+        return serde_json::from_slice(&bytes)
+            .with_context(|| format!("failed to deserialize receipt {}", path.display()));
+    }
+    bincode::deserialize(&bytes)
+        .with_context(|| format!("failed to deserialize receipt {}", path.display()))
+}
+
+/// Format `prove-range` writes receipts in.
+#[derive(ValueEnum, Debug, Clone, Copy, Default)]
+pub(crate) enum ReceiptFormat {
+    /// Receipt serialized with bincode, as read by `verify-receipt` and `post-receipt`
+    #[default]
+    Bincode,
+    /// Receipt serialized as JSON
+    Json,
+    /// JSON with the journal, the encoded seal and the calldata of the `updateRange` call that
+    /// posts the receipt, for submitting it with other tools
+    Calldata,
+}
+
+impl ReceiptFormat {
+    pub(crate) fn encode(self, receipt: &Receipt) -> anyhow::Result<Vec<u8>> {
+        match self {
+            ReceiptFormat::Bincode => Ok(bincode::serialize(receipt)?),
+            ReceiptFormat::Json => Ok(serde_json::to_vec_pretty(receipt)?),
+            ReceiptFormat::Calldata => {
+                let seal = encode_seal(receipt)?;
+                let journal = receipt.journal.bytes.clone();
+                let commitment = RangeCommitment::abi_decode_validate(&journal)
+                    .context("journal is not a range commitment")?;
+                let calldata = IBlobstream::updateRangeCall {
+                    _commitBytes: journal.clone().into(),
+                    _seal: seal.clone().into(),
+                }
+                .abi_encode();
+                let output = serde_json::json!({
+                    "function": "updateRange(bytes,bytes)",
+                    "journal": hex::encode_prefixed(&journal),
+                    "seal": hex::encode_prefixed(&seal),
+                    "calldata": hex::encode_prefixed(&calldata),
+                    "commitment": commitment_json(&commitment),
+                });
+                Ok(serde_json::to_vec_pretty(&output)?)
+            }
+        }
+    }
+}
+
+/// JSON representation of a range commitment, with field names matching the Solidity struct.
+pub(crate) fn commitment_json(commitment: &RangeCommitment) -> serde_json::Value {
+    serde_json::json!({
+        "trustedHeaderHash": commitment.trustedHeaderHash.to_string(),
+        "newHeight": commitment.newHeight,
+        "newHeaderHash": commitment.newHeaderHash.to_string(),
+        "merkleRoot": commitment.merkleRoot.to_string(),
+        "validatorBitmap": format!("{:#x}", commitment.validatorBitmap),
+        "chainIdHash": commitment.chainIdHash.to_string(),
+        "trustedHeight": commitment.trustedHeight,
+        "trustedHeaderTime": commitment.trustedHeaderTime,
+        "newHeaderTime": commitment.newHeaderTime,
+        "headerHashRoot": commitment.headerHashRoot.to_string(),
+        "leafContent": commitment.leafContent,
+    })
+}
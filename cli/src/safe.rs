@@ -0,0 +1,92 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Export of transactions as Safe Transaction Builder batches, for contracts owned by a Safe.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy::primitives::{Address, Bytes, U256};
+use anyhow::Context;
+use clap::Args;
+
+/// Options to export transactions to a Safe instead of sending them.
+#[derive(Args, Debug, Clone)]
+pub(crate) struct SafeExportArgs {
+    /// Write the transactions to this file as a Safe Transaction Builder batch instead of sending
+    /// them
+    #[clap(long, env, requires = "safe_address")]
+    safe_export: Option<PathBuf>,
+
+    /// Address of the Safe that executes the exported transactions
+    #[clap(long, env)]
+    safe_address: Option<Address>,
+}
+
+impl SafeExportArgs {
+    /// Path to export to and the address of the Safe, if exporting.
+    pub(crate) fn target(&self) -> Option<(&Path, Address)> {
+        Some((self.safe_export.as_deref()?, self.safe_address?))
+    }
+}
+
+/// Transaction executed by the Safe.
+pub(crate) struct SafeTransaction {
+    pub to: Address,
+    pub value: U256,
+    pub data: Bytes,
+}
+
+/// Writes a batch of transactions in the format imported by the Safe Transaction Builder.
+pub(crate) fn write_batch(
+    path: &Path,
+    chain_id: u64,
+    safe: Address,
+    name: &str,
+    description: &str,
+    transactions: &[SafeTransaction],
+) -> anyhow::Result<()> {
+    let created_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+    let batch = serde_json::json!({
+        "version": "1.0",
+        "chainId": chain_id.to_string(),
+        "createdAt": created_at,
+        "meta": {
+            "name": name,
+            "description": description,
+            "createdFromSafeAddress": safe.to_string(),
+        },
+        "transactions": transactions
+            .iter()
+            .map(|tx| serde_json::json!({
+                "to": tx.to.to_string(),
+                "value": tx.value.to_string(),
+                "data": tx.data.to_string(),
+                "contractMethod": null,
+                "contractInputsValues": null,
+            }))
+            .collect::<Vec<_>>(),
+    });
+    std::fs::write(path, serde_json::to_string_pretty(&batch)?)
+        .with_context(|| format!("failed to write Safe batch {}", path.display()))?;
+    println!(
+        "wrote {} transaction(s) for Safe {} to {}",
+        transactions.len(),
+        safe,
+        path.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,473 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! HTTP API serving data root inclusion proofs, compatible with the `data_root_inclusion_proof`
+//! endpoint of Celestia nodes.
+
+use std::{collections::BTreeMap, net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+
+use alloy::{primitives::Address, providers::ProviderBuilder};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use blobstream0_core::{fetch_headers, tendermint_client, IndexedCommitment, Indexer};
+use blobstream0_primitives::{
+    data_root_inclusion_proof, data_root_leaf_hash, proto::ProtoVersion, IBlobstream, LeafContent,
+};
+use clap::Parser;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tendermint::block::Header;
+use tendermint_rpc::HttpClient;
+use tokio::sync::Mutex;
+
+/// Maximum number of heights a proof can be requested over, matching the data commitment window
+/// of Celestia nodes.
+const MAX_PROOF_RANGE: u64 = 10_000;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub(crate) struct ServeArgs {
+    /// Address to listen on
+    #[clap(long, env, default_value = "127.0.0.1:8080")]
+    listen_address: SocketAddr,
+
+    /// The Tendermint RPC URL, which headers are fetched from
+    #[clap(long, env)]
+    tendermint_rpc: String,
+
+    /// Maximum number of headers to keep cached
+    #[clap(long, env, default_value_t = 20_000)]
+    header_cache_size: usize,
+
+    /// Path to the index database, which enables proofs by nonce and checking proofs against
+    /// the data commitments stored in the contract
+    #[clap(long, env)]
+    index_db: Option<PathBuf>,
+
+    /// The Ethereum RPC URL, to keep the index synced while serving
+    #[clap(long, env, requires_all = ["index_db", "eth_address"])]
+    eth_rpc: Option<String>,
+
+    /// The deployed contract on Ethereum to index
+    #[clap(long, env)]
+    eth_address: Option<Address>,
+
+    /// Ethereum block to start indexing from when the database is empty
+    #[clap(long, env, default_value_t = 0)]
+    from_block: u64,
+
+    /// Interval in seconds to sync the index with the contract
+    #[clap(long, env, default_value_t = 12)]
+    index_interval: u64,
+
+    /// Header field committed for each block: data-hash, app-hash or header-hash. Must match the
+    /// leaf content the contract was deployed with.
+    #[clap(long, env, default_value_t)]
+    leaf_content: LeafContent,
+
+    /// CometBFT protobuf version of the Tendermint chain: 0.34, 0.37 or 0.38
+    #[clap(long, env, default_value_t)]
+    proto_version: ProtoVersion,
+}
+
+impl ServeArgs {
+    pub(crate) async fn start(self) -> anyhow::Result<()> {
+        let indexer = match &self.index_db {
+            Some(path) => Some(Arc::new(Indexer::open(path, self.from_block)?)),
+            None => None,
+        };
+
+        if let (Some(indexer), Some(eth_rpc), Some(eth_address)) =
+            (indexer.clone(), &self.eth_rpc, self.eth_address)
+        {
+            let provider = ProviderBuilder::new().connect_http(eth_rpc.parse()?);
+            let contract = IBlobstream::new(eth_address, provider);
+            let interval = Duration::from_secs(self.index_interval);
+            tokio::spawn(async move {
+                if let Err(e) = indexer.run(&contract, interval).await {
+                    tracing::error!(target: "blobstream0::cli", "Indexer stopped: {:?}", e);
+                }
+            });
+        }
+
+        let state = Arc::new(ServeState {
+            client: Arc::new(tendermint_client(&self.tendermint_rpc, self.proto_version)?),
+            headers: HeaderCache::new(self.header_cache_size),
+            indexer,
+            leaf_content: self.leaf_content,
+        });
+        let app = Router::new()
+            .route("/data_root_inclusion_proof", get(range_proof))
+            .route("/data_root_inclusion_proof_by_nonce", get(nonce_proof))
+            .with_state(state);
+
+        let listener = tokio::net::TcpListener::bind(self.listen_address).await?;
+        tracing::info!(target: "blobstream0::cli", "Serving proofs on {}", self.listen_address);
+        axum::serve(listener, app).await?;
+
+        Ok(())
+    }
+}
+
+struct ServeState {
+    client: Arc<HttpClient>,
+    headers: HeaderCache,
+    indexer: Option<Arc<Indexer>>,
+    leaf_content: LeafContent,
+}
+
+/// Cache of fetched headers by height. The lowest heights are evicted first, as proofs are most
+/// often requested for recent heights.
+///
+/// The lock is not held while missing headers are fetched, so that a slow Tendermint RPC does not
+/// block requests for cached headers. Concurrent requests for the same missing heights may each
+/// fetch them.
+struct HeaderCache {
+    headers: Mutex<BTreeMap<u64, Header>>,
+    capacity: usize,
+}
+
+impl HeaderCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            headers: Mutex::new(BTreeMap::new()),
+            capacity,
+        }
+    }
+
+    /// Returns the headers for heights `start..end`, fetching any that are not cached.
+    async fn get(
+        &self,
+        client: Arc<HttpClient>,
+        start: u64,
+        end: u64,
+    ) -> anyhow::Result<Vec<Header>> {
+        // Cached headers are copied out, so they cannot be evicted before being returned.
+        let mut range: BTreeMap<u64, Header> = self
+            .headers
+            .lock()
+            .await
+            .range(start..end)
+            .map(|(height, header)| (*height, header.clone()))
+            .collect();
+        let missing: Vec<u64> = (start..end)
+            .filter(|height| !range.contains_key(height))
+            .collect();
+
+        if let (Some(first), Some(last)) = (missing.first(), missing.last()) {
+            let fetched = fetch_headers(client, *first..*last + 1).await?;
+            let mut headers = self.headers.lock().await;
+            for header in fetched {
+                headers.insert(header.height.value(), header.clone());
+                range.insert(header.height.value(), header);
+            }
+            while headers.len() > self.capacity {
+                headers.pop_first();
+            }
+        }
+
+        (start..end)
+            .map(|height| range.remove(&height))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| anyhow::anyhow!("missing fetched header"))
+    }
+}
+
+#[derive(Deserialize)]
+struct RangeParams {
+    height: u64,
+    start: u64,
+    end: u64,
+}
+
+#[derive(Deserialize)]
+struct NonceParams {
+    nonce: u64,
+    height: u64,
+}
+
+/// Proof of the data root of `height` in the data root tuple root of heights `start..end`, in the
+/// same format as Celestia nodes.
+async fn range_proof(
+    State(state): State<Arc<ServeState>>,
+    Query(params): Query<RangeParams>,
+) -> Result<Json<Value>, ApiError> {
+    let RangeParams { height, start, end } = params;
+    if start >= end || end - start > MAX_PROOF_RANGE {
+        return Err(ApiError::bad_request(format!(
+            "range must be non empty and at most {} heights",
+            MAX_PROOF_RANGE
+        )));
+    }
+    if !(start..end).contains(&height) {
+        return Err(ApiError::bad_request(format!(
+            "height {} must be in the range {}..{}",
+            height, start, end
+        )));
+    }
+
+    // If the range matches a commitment in the contract, check against it.
+    let commitment = match &state.indexer {
+        Some(indexer) => indexer.commitment_for_height(height)?.filter(|indexed| {
+            indexed.commitment.start_block + 1 == start && indexed.commitment.end_block + 1 == end
+        }),
+        None => None,
+    };
+
+    let proof = build_proof(&state, start, end, height, commitment.as_ref()).await?;
+    Ok(Json(json!({
+        "jsonrpc": "2.0",
+        "id": -1,
+        "result": { "proof": proof },
+    })))
+}
+
+/// Proof of the data root of `height` in the data commitment stored at `nonce` in the contract.
+async fn nonce_proof(
+    State(state): State<Arc<ServeState>>,
+    Query(params): Query<NonceParams>,
+) -> Result<Json<Value>, ApiError> {
+    let Some(indexer) = &state.indexer else {
+        return Err(ApiError::bad_request(
+            "proofs by nonce require an index database".to_string(),
+        ));
+    };
+    let indexed = indexer.commitment(params.nonce)?.ok_or_else(|| {
+        ApiError::not_found(format!("no commitment indexed at nonce {}", params.nonce))
+    })?;
+    if !indexed.commitment.contains(params.height) {
+        return Err(ApiError::bad_request(format!(
+            "height {} is not in the commitment at nonce {}, which includes heights {}..={}",
+            params.height,
+            params.nonce,
+            indexed.commitment.start_block + 1,
+            indexed.commitment.end_block
+        )));
+    }
+
+    let start = indexed.commitment.start_block + 1;
+    let end = indexed.commitment.end_block + 1;
+    let proof = build_proof(&state, start, end, params.height, Some(&indexed)).await?;
+    Ok(Json(json!({
+        "jsonrpc": "2.0",
+        "id": -1,
+        "result": {
+            "proof_nonce": params.nonce.to_string(),
+            "start": start.to_string(),
+            "end": end.to_string(),
+            "data_commitment": BASE64.encode(indexed.commitment.data_commitment),
+            "proof": proof,
+        },
+    })))
+}
+
+/// Builds the proof of the data root of `height` in heights `start..end`, checking that the
+/// headers hash link to each other, and match the contract commitment if known.
+async fn build_proof(
+    state: &ServeState,
+    start: u64,
+    end: u64,
+    height: u64,
+    commitment: Option<&IndexedCommitment>,
+) -> Result<Value, ApiError> {
+    let headers = state.headers.get(state.client.clone(), start, end).await?;
+    for pair in headers.windows(2) {
+        let linked = pair[1].last_block_id.map(|id| id.hash) == Some(pair[0].hash());
+        if !linked {
+            return Err(ApiError::internal(format!(
+                "header at height {} does not hash link to the previous header",
+                pair[1].height
+            )));
+        }
+    }
+
+    let (root, tuple, proof) = data_root_inclusion_proof(&headers, height, state.leaf_content)
+        .ok_or_else(|| ApiError::internal(format!("header for height {} not found", height)))?;
+
+    if let Some(indexed) = commitment {
+        let last_hash = headers.last().map(|header| header.hash());
+        if indexed.commitment.data_commitment != root
+            || last_hash.map(|hash| hash.as_bytes() == indexed.header_hash.as_slice()) != Some(true)
+        {
+            return Err(ApiError::internal(format!(
+                "headers do not match the commitment at nonce {}",
+                indexed.commitment.proof_nonce
+            )));
+        }
+    }
+
+    Ok(json!({
+        "total": proof.numLeaves.to_string(),
+        "index": proof.key.to_string(),
+        "leaf_hash": BASE64.encode(data_root_leaf_hash(&tuple)),
+        "aunts": proof
+            .sideNodes
+            .iter()
+            .map(|node| BASE64.encode(node))
+            .collect::<Vec<_>>(),
+    }))
+}
+
+/// Error returned in the JSON-RPC error format of Celestia nodes.
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn bad_request(message: String) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message,
+        }
+    }
+
+    fn not_found(message: String) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message,
+        }
+    }
+
+    fn internal(message: String) -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message,
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        Self::internal(format!("{:#}", err))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": -1,
+            "error": {
+                "code": -32603,
+                "message": self.message,
+            },
+        });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tendermint::{
+        account, block,
+        block::header::Version,
+        hash::{AppHash, Hash},
+        Time,
+    };
+
+    use super::*;
+
+    /// Headers for heights `start..end`, each hash linked to the previous one.
+    fn linked_headers(start: u64, end: u64) -> Vec<Header> {
+        let mut headers: Vec<Header> = Vec::new();
+        for height in start..end {
+            let header = Header {
+                version: Version { block: 11, app: 1 },
+                chain_id: "test-chain".parse().unwrap(),
+                height: height.try_into().unwrap(),
+                time: Time::from_unix_timestamp(height as i64, 0).unwrap(),
+                last_block_id: headers.last().map(|last| block::Id {
+                    hash: last.hash(),
+                    part_set_header: Default::default(),
+                }),
+                last_commit_hash: None,
+                data_hash: Some(Hash::Sha256([height as u8; 32])),
+                validators_hash: Hash::Sha256([1; 32]),
+                next_validators_hash: Hash::Sha256([1; 32]),
+                consensus_hash: Hash::Sha256([2; 32]),
+                app_hash: AppHash::default(),
+                last_results_hash: None,
+                evidence_hash: None,
+                proposer_address: account::Id::new([0; 20]),
+            };
+            headers.push(header);
+        }
+        headers
+    }
+
+    /// State with the headers cached, and an RPC client that cannot be connected to.
+    async fn state(headers: Vec<Header>) -> Arc<ServeState> {
+        let cache = HeaderCache::new(headers.len());
+        cache.headers.lock().await.extend(
+            headers
+                .into_iter()
+                .map(|header| (header.height.value(), header)),
+        );
+        Arc::new(ServeState {
+            client: Arc::new(HttpClient::new("http://127.0.0.1:1").unwrap()),
+            headers: cache,
+            indexer: None,
+            leaf_content: LeafContent::DataHash,
+        })
+    }
+
+    #[tokio::test]
+    async fn range_proof_from_cached_headers() {
+        let state = state(linked_headers(10, 15)).await;
+        let params = RangeParams {
+            height: 12,
+            start: 10,
+            end: 15,
+        };
+        let Ok(Json(body)) = range_proof(State(state), Query(params)).await else {
+            panic!("proof of cached headers failed");
+        };
+        let proof = &body["result"]["proof"];
+        assert_eq!(proof["total"], "5");
+        assert_eq!(proof["index"], "2");
+        assert_eq!(proof["aunts"].as_array().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn range_proof_rejects_invalid_range() {
+        let state = state(Vec::new()).await;
+        for (height, start, end) in [(10, 10, 10), (15, 10, 15), (0, 0, MAX_PROOF_RANGE + 1)] {
+            let params = RangeParams { height, start, end };
+            let Err(err) = range_proof(State(state.clone()), Query(params)).await else {
+                panic!("range {start}..{end} with height {height} was accepted");
+            };
+            assert_eq!(err.status, StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[tokio::test]
+    async fn nonce_proof_requires_index() {
+        let state = state(Vec::new()).await;
+        let params = NonceParams {
+            nonce: 1,
+            height: 10,
+        };
+        let Err(err) = nonce_proof(State(state), Query(params)).await else {
+            panic!("proof by nonce without an index was accepted");
+        };
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -18,8 +18,11 @@ use std::time::{Duration, Instant};
 
 use alloy::{network::Network, primitives::FixedBytes, providers::Provider};
 use anyhow::Context;
-use blobstream0_core::{post_batch, prove_block_range};
-use blobstream0_primitives::IBlobstream::IBlobstreamInstance;
+use blobstream0_core::{post_batch, prove_and_post_block_ranges, prove_block_range, ContractError};
+use blobstream0_primitives::{
+    IBlobstream::{IBlobstreamErrors, IBlobstreamInstance},
+    LeafContent, ProofConfig,
+};
 use rand::Rng;
 use tendermint_rpc::{Client, HttpClient};
 use tokio::task::JoinError;
@@ -28,6 +31,8 @@ pub(crate) struct BlobstreamService<P, N> {
     contract: Arc<IBlobstreamInstance<P, N>>,
     tm_client: Arc<HttpClient>,
     batch_size: u64,
+    catch_up_parallelism: Option<usize>,
+    proof_config: ProofConfig,
 }
 
 impl<P, N> BlobstreamService<P, N> {
@@ -40,8 +45,23 @@ impl<P, N> BlobstreamService<P, N> {
             contract: Arc::new(contract),
             tm_client: Arc::new(tm_client),
             batch_size,
+            catch_up_parallelism: None,
+            proof_config: ProofConfig::default(),
         }
     }
+
+    /// Enables catch-up mode, which proves all available batches concurrently when the contract is
+    /// more than one batch behind.
+    pub fn with_catch_up_parallelism(mut self, parallelism: Option<usize>) -> Self {
+        self.catch_up_parallelism = parallelism;
+        self
+    }
+
+    /// Sets the config blocks are proven with, which must match the leaf content of the contract.
+    pub fn with_proof_config(mut self, config: ProofConfig) -> Self {
+        self.proof_config = config;
+        self
+    }
 }
 
 impl<P, N> BlobstreamService<P, N>
@@ -111,22 +131,80 @@ where
                 continue;
             }
 
+            if let Some(parallelism) = self.catch_up_parallelism {
+                let backlog_batches = (tm_height - trusted_height) / self.batch_size;
+                if backlog_batches > 1 {
+                    let catch_up_height = trusted_height + backlog_batches * self.batch_size;
+                    tracing::info!(
+                        target: "blobstream0::service",
+                        "Contract is {backlog_batches} batches behind, catching up to height {}",
+                        catch_up_height - 1
+                    );
+                    prove_and_post_block_ranges(
+                        self.tm_client.clone(),
+                        &self.contract,
+                        trusted_height..catch_up_height,
+                        self.batch_size,
+                        parallelism,
+                        self.proof_config,
+                    )
+                    .await
+                    .context("failed to catch up")?;
+                    return Ok(());
+                }
+            }
+
             break (trusted_height, untrusted_height);
         };
 
-        let receipt = prove_block_range(self.tm_client.clone(), trusted_height..untrusted_height)
-            .await
-            .context("failed to prove block range")?;
+        let receipt = prove_block_range(
+            self.tm_client.clone(),
+            trusted_height..untrusted_height,
+            self.proof_config,
+        )
+        .await
+        .context("failed to prove block range")?;
         post_batch(&self.contract, &receipt)
             .await
+            .inspect_err(|e| {
+                if let Some(ContractError::Blobstream(
+                    IBlobstreamErrors::InvalidTrustedHeaderHash(_)
+                    | IBlobstreamErrors::InvalidTrustedHeight(_)
+                    | IBlobstreamErrors::InvalidTargetHeight(_),
+                )) = e.downcast_ref()
+                {
+                    tracing::warn!(
+                        target: "blobstream0::service",
+                        "Contract state changed while proving, the range will be re-proven from the new state"
+                    );
+                }
+            })
             .context("failed to post batch")?;
 
         // TODO ensure height is updated as a sanity check
         Ok(())
     }
 
+    /// Checks the proof config against the contract, to fail before proving ranges that the
+    /// contract would reject.
+    async fn check_proof_config(&self) -> anyhow::Result<()> {
+        let leaf_content =
+            exponential_backoff(|| async { Ok(self.contract.leafContent().call().await?) })
+                .await
+                .context("failed to read the leaf content of the contract")?;
+        let leaf_content = LeafContent::try_from(leaf_content)?;
+        anyhow::ensure!(
+            leaf_content == self.proof_config.leaf_content,
+            "leaf content {} of the proof config does not match the leaf content {} of the contract",
+            self.proof_config.leaf_content,
+            leaf_content
+        );
+        Ok(())
+    }
+
     /// Spawn blobstream service, which will run indefinitely until a fatal error when awaited.
     pub async fn spawn(&self) -> anyhow::Result<()> {
+        self.check_proof_config().await?;
         loop {
             exponential_backoff(|| async {
                 let res = self.progress_contract_state().await;
@@ -13,11 +13,11 @@
 // limitations under the License.
 
 use self::blobstream::BlobstreamService;
-use super::setup_provider;
-use alloy::{primitives::Address, providers::ProviderBuilder};
+use crate::{signer::SignerArgs, ProofConfigArgs};
+use alloy::primitives::Address;
+use blobstream0_core::tendermint_client;
 use blobstream0_primitives::IBlobstream;
 use clap::Parser;
-use tendermint_rpc::HttpClient;
 
 mod blobstream;
 
@@ -32,35 +32,45 @@ pub(crate) struct ServiceArgs {
     #[clap(long, env)]
     eth_rpc: String,
 
-    /// The deployed contract on Ethereum to reference
+    /// Expected chain ID of the Ethereum RPC, checked before sending any transactions
     #[clap(long, env)]
-    eth_address: Address,
+    eth_chain_id: Option<u64>,
 
-    #[cfg(feature = "fireblocks")]
-    /// Fireblocks signer address.
+    /// The deployed contract on Ethereum to reference
     #[clap(long, env)]
-    fireblocks_address: String,
+    eth_address: Address,
 
-    #[cfg(not(feature = "fireblocks"))]
-    /// Hex encoded private key to use for deploying.
-    #[clap(long, env)]
-    private_key_hex: String,
+    #[clap(flatten)]
+    signer: SignerArgs,
 
     /// Number of blocks proved in each batch of block headers
     #[clap(long, env)]
     batch_size: u64,
+
+    /// Enables catch-up mode with the number of batches to prove concurrently when the contract is
+    /// more than one batch behind the Tendermint chain. Proofs are still posted in order.
+    #[clap(long, env)]
+    catch_up_parallelism: Option<usize>,
+
+    #[clap(flatten)]
+    proof_config: ProofConfigArgs,
 }
 
 impl ServiceArgs {
     pub(crate) async fn start(self) -> anyhow::Result<()> {
-        let tm_client = HttpClient::new(self.tendermint_rpc.as_str())?;
+        let tm_client = tendermint_client(&self.tendermint_rpc, self.proof_config.proto_version)?;
 
-        let (provider, _) = setup_provider!(self);
+        let (provider, _) = self
+            .signer
+            .connect(&self.eth_rpc, self.eth_chain_id)
+            .await?;
 
         let contract = IBlobstream::new(self.eth_address, provider);
 
         tracing::info!(target: "blobstream0::service", "Starting service");
         BlobstreamService::new(contract, tm_client, self.batch_size)
+            .with_catch_up_parallelism(self.catch_up_parallelism)
+            .with_proof_config(self.proof_config.into())
             .spawn()
             .await?;
 
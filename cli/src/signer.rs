@@ -0,0 +1,173 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Construction of Ethereum providers that sign transactions with the configured signer source.
+
+use alloy::{
+    primitives::Address,
+    providers::{Provider, ProviderBuilder},
+};
+use clap::Args;
+
+#[cfg(not(feature = "fireblocks"))]
+use std::path::{Path, PathBuf};
+
+#[cfg(not(feature = "fireblocks"))]
+use alloy::signers::local::PrivateKeySigner;
+#[cfg(not(feature = "fireblocks"))]
+use anyhow::{bail, Context};
+
+/// Source of the key used to sign transactions. Exactly one source must be configured.
+#[cfg(not(feature = "fireblocks"))]
+#[derive(Args, Debug, Clone)]
+pub(crate) struct SignerArgs {
+    /// Path to an encrypted JSON keystore to sign transactions with
+    #[clap(long, env)]
+    keystore: Option<PathBuf>,
+
+    /// File containing the keystore password. The password is prompted for if not set.
+    #[clap(long, env)]
+    keystore_password_file: Option<PathBuf>,
+
+    /// Path to a file containing the hex encoded private key, such as a mounted secret
+    #[clap(long, env)]
+    private_key_file: Option<PathBuf>,
+
+    /// Hex encoded private key. This is visible in process lists and env files, so it is only
+    /// accepted with `--allow-plaintext-key`.
+    #[clap(long, env)]
+    private_key_hex: Option<String>,
+
+    /// Allow signing with the plain text key from `--private-key-hex`
+    #[clap(long, env)]
+    allow_plaintext_key: bool,
+}
+
+/// Fireblocks account used to sign transactions through the Fireblocks JSON-RPC proxy.
+#[cfg(feature = "fireblocks")]
+#[derive(Args, Debug, Clone)]
+pub(crate) struct SignerArgs {
+    /// Fireblocks signer address.
+    #[clap(long, env)]
+    fireblocks_address: String,
+
+    #[clap(flatten)]
+    fee_policy: crate::fireblocks::FeePolicy,
+}
+
+#[cfg(not(feature = "fireblocks"))]
+impl SignerArgs {
+    /// Loads the signer from the configured source.
+    fn local_signer(&self) -> anyhow::Result<PrivateKeySigner> {
+        match (
+            &self.keystore,
+            &self.private_key_file,
+            &self.private_key_hex,
+        ) {
+            (Some(path), None, None) => {
+                let password = match &self.keystore_password_file {
+                    Some(password_file) => read_secret_file(password_file)?,
+                    None => rpassword::prompt_password(format!(
+                        "Password for keystore {}: ",
+                        path.display()
+                    ))?,
+                };
+                PrivateKeySigner::decrypt_keystore(path, password)
+                    .with_context(|| format!("failed to decrypt keystore {}", path.display()))
+            }
+            (None, Some(path), None) => read_secret_file(path)?
+                .parse()
+                .with_context(|| format!("invalid private key in {}", path.display())),
+            (None, None, Some(private_key_hex)) => {
+                anyhow::ensure!(
+                    self.allow_plaintext_key,
+                    "--private-key-hex requires --allow-plaintext-key, prefer --keystore or \
+                     --private-key-file"
+                );
+                Ok(private_key_hex.parse()?)
+            }
+            (None, None, None) => bail!(
+                "no signer configured, use one of --keystore, --private-key-file or \
+                 --private-key-hex"
+            ),
+            _ => {
+                bail!("only one of --keystore, --private-key-file or --private-key-hex can be used")
+            }
+        }
+    }
+
+    /// Connects to the Ethereum RPC with a provider that signs transactions with the configured
+    /// key. Returns the provider and the signer address.
+    pub(crate) async fn connect(
+        &self,
+        eth_rpc: &str,
+        eth_chain_id: Option<u64>,
+    ) -> anyhow::Result<(impl Provider + Clone + 'static, Address)> {
+        let signer = self.local_signer()?;
+        let signer_address = signer.address();
+        let provider = ProviderBuilder::new()
+            .wallet(alloy::network::EthereumWallet::from(signer))
+            .connect_http(eth_rpc.parse()?);
+        ensure_chain_id(&provider, eth_chain_id).await?;
+        Ok((provider, signer_address))
+    }
+}
+
+#[cfg(feature = "fireblocks")]
+impl SignerArgs {
+    /// Connects to the Ethereum RPC, which is expected to be the Fireblocks JSON-RPC proxy, with a
+    /// provider that sends transactions from the Fireblocks address. Returns the provider and the
+    /// signer address.
+    pub(crate) async fn connect(
+        &self,
+        eth_rpc: &str,
+        eth_chain_id: Option<u64>,
+    ) -> anyhow::Result<(impl Provider + Clone + 'static, Address)> {
+        let fireblocks_address: Address = self.fireblocks_address.parse()?;
+        let provider = ProviderBuilder::new()
+            .fetch_chain_id()
+            .filler(crate::fireblocks::FireblocksFiller {
+                sender: fireblocks_address,
+                fee_policy: self.fee_policy,
+            })
+            .connect_http(eth_rpc.parse()?);
+        ensure_chain_id(&provider, eth_chain_id).await?;
+        Ok((provider, fireblocks_address))
+    }
+}
+
+/// Reads a secret from a file, ignoring surrounding whitespace.
+#[cfg(not(feature = "fireblocks"))]
+fn read_secret_file(path: &Path) -> anyhow::Result<String> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(contents.trim().to_string())
+}
+
+/// Checks that the Ethereum RPC is connected to the expected chain, if one is configured.
+pub(crate) async fn ensure_chain_id<P: Provider>(
+    provider: &P,
+    expected: Option<u64>,
+) -> anyhow::Result<()> {
+    if let Some(expected) = expected {
+        let chain_id = provider.get_chain_id().await?;
+        anyhow::ensure!(
+            chain_id == expected,
+            "Ethereum RPC chain ID {} does not match expected chain ID {}",
+            chain_id,
+            expected
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,258 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Overview of the deployed contract state compared with the Tendermint chain.
+
+use std::fmt;
+
+use alloy::{
+    primitives::{utils::format_ether, Address, B256, U256},
+    providers::{Provider, ProviderBuilder},
+};
+use blobstream0_core::{light_client_image_id, tendermint_client};
+use blobstream0_primitives::{chain_id_hash, proto::ProtoVersion, IBlobstream, LeafContent};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use tendermint_rpc::Client;
+
+use crate::{MockVerifier, RiscZeroGroth16Verifier};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub(crate) struct StatusArgs {
+    /// The Tendermint RPC URL
+    #[clap(long, env)]
+    tendermint_rpc: String,
+
+    /// CometBFT protobuf version of the Tendermint chain: 0.34, 0.37 or 0.38
+    #[clap(long, env, default_value_t)]
+    proto_version: ProtoVersion,
+
+    /// The Ethereum RPC URL
+    #[clap(long, env)]
+    eth_rpc: String,
+
+    /// The deployed contract on Ethereum to reference
+    #[clap(long, env)]
+    eth_address: Address,
+
+    /// Address of the signer posting proofs, to include its balance
+    #[clap(long, env)]
+    signer_address: Option<Address>,
+
+    /// Print the status as JSON
+    #[clap(long)]
+    json: bool,
+}
+
+/// Kind of RISC Zero verifier the contract verifies proofs with.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum VerifierKind {
+    Groth16,
+    /// Mock verifier, which accepts fake receipts generated in dev mode.
+    Mock,
+    /// Neither verifier could be detected, such as when using a verifier router.
+    Unknown,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Status {
+    contract_address: Address,
+    latest_height: u64,
+    latest_block_hash: B256,
+    proof_nonce: String,
+    min_batch_size: u64,
+    tendermint_height: u64,
+    lag_blocks: u64,
+    /// Seconds between the time of the latest contract header and the Tendermint tip.
+    lag_seconds: Option<u64>,
+    image_id: B256,
+    local_image_id: B256,
+    chain_id_hash: B256,
+    /// Chain ID of the Tendermint RPC and its hash, to compare with the contract chain ID hash.
+    tendermint_chain_id: String,
+    tendermint_chain_id_hash: B256,
+    /// Header field committed for each block, or the raw value if unknown to this CLI.
+    leaf_content: String,
+    verifier: Address,
+    verifier_kind: VerifierKind,
+    owner: Address,
+    /// Pending owner of a two step ownership transfer, if one has been started.
+    pending_owner: Option<Address>,
+    signer_address: Option<Address>,
+    /// Balance of the signer in wei.
+    signer_balance: Option<String>,
+}
+
+impl StatusArgs {
+    pub(crate) async fn run(self) -> anyhow::Result<()> {
+        let tm_client = tendermint_client(&self.tendermint_rpc, self.proto_version)?;
+        let provider = ProviderBuilder::new().connect_http(self.eth_rpc.parse()?);
+        let contract = IBlobstream::new(self.eth_address, &provider);
+
+        let (
+            latest_height,
+            latest_block_hash,
+            proof_nonce,
+            min_batch_size,
+            image_id,
+            verifier,
+            owner,
+            pending_owner,
+            contract_chain_id_hash,
+            leaf_content,
+        ) = tokio::try_join!(
+            async { contract.latestHeight().call().await },
+            async { contract.latestBlockHash().call().await },
+            async { contract.proofNonce().call().await },
+            async { contract.minBatchSize().call().await },
+            async { contract.imageId().call().await },
+            async { contract.verifier().call().await },
+            async { contract.owner().call().await },
+            async { contract.pendingOwner().call().await },
+            async { contract.chainIdHash().call().await },
+            async { contract.leafContent().call().await },
+        )?;
+
+        let (tm_status, contract_header) = tokio::try_join!(
+            tm_client.status(),
+            tm_client.header(u32::try_from(latest_height)?)
+        )?;
+        let tendermint_height = tm_status.sync_info.latest_block_height.value();
+        let lag_seconds = tm_status
+            .sync_info
+            .latest_block_time
+            .duration_since(contract_header.header.time)
+            .ok()
+            .map(|lag| lag.as_secs());
+
+        let signer_balance = match self.signer_address {
+            Some(address) => Some(provider.get_balance(address).await?.to_string()),
+            None => None,
+        };
+
+        let status = Status {
+            contract_address: self.eth_address,
+            latest_height,
+            latest_block_hash,
+            proof_nonce: proof_nonce.to_string(),
+            min_batch_size,
+            tendermint_height,
+            lag_blocks: tendermint_height.saturating_sub(latest_height),
+            lag_seconds,
+            image_id,
+            local_image_id: B256::from(<[u8; 32]>::from(light_client_image_id()?)),
+            chain_id_hash: contract_chain_id_hash,
+            tendermint_chain_id: contract_header.header.chain_id.to_string(),
+            tendermint_chain_id_hash: chain_id_hash(&contract_header.header.chain_id).into(),
+            leaf_content: LeafContent::try_from(leaf_content)
+                .map(|leaf_content| leaf_content.to_string())
+                .unwrap_or_else(|_| leaf_content.to_string()),
+            verifier,
+            verifier_kind: detect_verifier(&provider, verifier).await,
+            owner,
+            pending_owner: (pending_owner != Address::ZERO).then_some(pending_owner),
+            signer_address: self.signer_address,
+            signer_balance,
+        };
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&status)?);
+        } else {
+            print!("{}", status);
+        }
+
+        Ok(())
+    }
+}
+
+/// Detects the kind of verifier from the public values only the Groth16 and mock verifiers have.
+pub(crate) async fn detect_verifier<P: Provider>(provider: &P, verifier: Address) -> VerifierKind {
+    if RiscZeroGroth16Verifier::new(verifier, provider)
+        .BN254_CONTROL_ID()
+        .call()
+        .await
+        .is_ok()
+    {
+        VerifierKind::Groth16
+    } else if MockVerifier::new(verifier, provider)
+        .SELECTOR()
+        .call()
+        .await
+        .is_ok()
+    {
+        VerifierKind::Mock
+    } else {
+        VerifierKind::Unknown
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "contract:          {}", self.contract_address)?;
+        writeln!(f, "latest height:     {}", self.latest_height)?;
+        writeln!(f, "latest block hash: {}", self.latest_block_hash)?;
+        writeln!(f, "tendermint height: {}", self.tendermint_height)?;
+        match self.lag_seconds {
+            Some(seconds) => writeln!(
+                f,
+                "lag:               {} blocks, {}s",
+                self.lag_blocks, seconds
+            )?,
+            None => writeln!(f, "lag:               {} blocks", self.lag_blocks)?,
+        }
+        writeln!(f, "proof nonce:       {}", self.proof_nonce)?;
+        writeln!(f, "min batch size:    {}", self.min_batch_size)?;
+        let image_id_note = if self.image_id == self.local_image_id {
+            "matches local guest".to_string()
+        } else {
+            format!("MISMATCH, local guest is {}", self.local_image_id)
+        };
+        writeln!(
+            f,
+            "image ID:          {} ({})",
+            self.image_id, image_id_note
+        )?;
+        let chain_id_note = if self.chain_id_hash == self.tendermint_chain_id_hash {
+            format!("matches {}", self.tendermint_chain_id)
+        } else {
+            format!("MISMATCH, Tendermint RPC is {}", self.tendermint_chain_id)
+        };
+        writeln!(
+            f,
+            "chain ID hash:     {} ({})",
+            self.chain_id_hash, chain_id_note
+        )?;
+        writeln!(f, "leaf content:      {}", self.leaf_content)?;
+        writeln!(
+            f,
+            "verifier:          {} ({:?})",
+            self.verifier, self.verifier_kind
+        )?;
+        writeln!(f, "owner:             {}", self.owner)?;
+        if let Some(pending_owner) = self.pending_owner {
+            writeln!(f, "pending owner:     {}", pending_owner)?;
+        }
+        if let (Some(address), Some(balance)) = (self.signer_address, &self.signer_balance) {
+            let balance = balance
+                .parse::<U256>()
+                .map(format_ether)
+                .unwrap_or_default();
+            writeln!(f, "signer:            {} ({} ETH)", address, balance)?;
+        }
+        Ok(())
+    }
+}
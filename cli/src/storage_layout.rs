@@ -0,0 +1,145 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Comparison of the storage layouts from forge artifacts, to check an upgrade does not move or
+//! retype the storage of the deployed implementation.
+
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+/// Storage layout of a contract, from the `storageLayout` field of a forge artifact.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct StorageLayout {
+    storage: Vec<StorageEntry>,
+    #[serde(default)]
+    types: BTreeMap<String, StorageType>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct StorageEntry {
+    label: String,
+    offset: u64,
+    slot: String,
+    #[serde(rename = "type")]
+    type_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct StorageType {
+    label: String,
+    number_of_bytes: String,
+}
+
+/// Differences between two storage layouts.
+#[derive(Debug, Default)]
+pub(crate) struct LayoutDiff {
+    /// Changes that would corrupt the existing storage.
+    pub errors: Vec<String>,
+    /// Changes that keep the storage intact, such as renamed variables.
+    pub warnings: Vec<String>,
+}
+
+impl StorageLayout {
+    /// Reads the storage layout of a forge artifact. Returns `None` if the artifact was built
+    /// without the `storageLayout` extra output.
+    pub(crate) fn from_artifact(artifact: &str) -> anyhow::Result<Option<Self>> {
+        let mut artifact: serde_json::Value =
+            serde_json::from_str(artifact).context("invalid forge artifact")?;
+        match artifact
+            .get_mut("storageLayout")
+            .map(serde_json::Value::take)
+        {
+            Some(layout) => Ok(Some(
+                serde_json::from_value(layout).context("invalid storage layout")?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Label and size of a type, which unlike the type ID do not include AST IDs that change
+    /// between compilations.
+    fn describe_type(&self, type_id: &str) -> (String, String) {
+        match self.types.get(type_id) {
+            Some(ty) => (ty.label.clone(), ty.number_of_bytes.clone()),
+            None => (type_id.to_string(), String::new()),
+        }
+    }
+
+    /// Compares the layout of a new implementation against this layout of the deployed one. Every
+    /// variable must keep its slot, offset and type, while new variables can be added in storage
+    /// that was unused.
+    pub(crate) fn compare(&self, new: &StorageLayout) -> LayoutDiff {
+        let mut diff = LayoutDiff::default();
+        for old_entry in &self.storage {
+            let Some(new_entry) = new
+                .storage
+                .iter()
+                .find(|entry| entry.slot == old_entry.slot && entry.offset == old_entry.offset)
+            else {
+                diff.errors.push(format!(
+                    "`{}` at slot {} offset {} was removed or moved",
+                    old_entry.label, old_entry.slot, old_entry.offset
+                ));
+                continue;
+            };
+
+            let old_type = self.describe_type(&old_entry.type_id);
+            let new_type = new.describe_type(&new_entry.type_id);
+            if old_type != new_type {
+                diff.errors.push(format!(
+                    "`{}` at slot {} changed type from `{}` to `{}`",
+                    old_entry.label, old_entry.slot, old_type.0, new_type.0
+                ));
+            }
+            if old_entry.label != new_entry.label {
+                diff.warnings.push(format!(
+                    "`{}` at slot {} was renamed to `{}`",
+                    old_entry.label, old_entry.slot, new_entry.label
+                ));
+            }
+        }
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTIFACT: &str = include_str!("../../contracts/artifacts/Blobstream0.json");
+
+    #[test]
+    fn manifest_layout_matches_artifact() {
+        let layout = StorageLayout::from_artifact(ARTIFACT).unwrap().unwrap();
+        let recorded: StorageLayout =
+            serde_json::from_str(&serde_json::to_string(&layout).unwrap()).unwrap();
+
+        let diff = recorded.compare(&layout);
+        assert!(diff.errors.is_empty(), "{:?}", diff.errors);
+        assert!(diff.warnings.is_empty(), "{:?}", diff.warnings);
+    }
+
+    #[test]
+    fn moved_variable_is_rejected() {
+        let old = StorageLayout::from_artifact(ARTIFACT).unwrap().unwrap();
+        let mut new = old.clone();
+        new.storage.remove(0);
+
+        let diff = old.compare(&new);
+        assert_eq!(diff.errors.len(), 1, "{:?}", diff.errors);
+    }
+}
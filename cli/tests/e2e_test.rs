@@ -22,9 +22,15 @@ use alloy::{
     signers::local::PrivateKeySigner,
 };
 use alloy_sol_types::{sol, SolCall};
-use blobstream0_core::{post_batch, prove_block_range};
+use blobstream0_core::{
+    data_root_inclusion_proof, execute_block_range, header_hash_inclusion_proof, post_batch,
+    prove_block_range, tendermint_client, ContractError,
+};
 use blobstream0_primitives::IBlobstream::{
-    self, BinaryMerkleProof, DataRootTuple, IBlobstreamInstance,
+    self, BinaryMerkleProof, DataRootTuple, IBlobstreamErrors, IBlobstreamInstance,
+};
+use blobstream0_primitives::{
+    chain_id_hash, header_time, proto::ProtoVersion, LeafContent, ProofConfig,
 };
 use reqwest::header;
 use serde::{Deserialize, Serialize};
@@ -71,8 +77,16 @@ async fn setup_test_environment() -> anyhow::Result<(
     // Set dev mode for test.
     std::env::set_var("RISC0_DEV_MODE", "true");
 
-    // Spin up a local Anvil node.
-    let anvil = Anvil::new().try_spawn()?;
+    let tm_client = Arc::new(HttpClient::new(CELESTIA_RPC_URL)?);
+    let trusted_header = tm_client.header(BATCH_START - 1).await?.header;
+    let trusted_block_hash = trusted_header.hash().as_bytes().try_into().unwrap();
+
+    // Spin up a local Anvil node, starting shortly after the trusted header so that it is within
+    // the trusting period of the contract.
+    let anvil = Anvil::new()
+        .arg("--timestamp")
+        .arg((header_time(&trusted_header) + 60).to_string())
+        .try_spawn()?;
 
     // Set up signer from the first default Anvil account (Alice).
     let signer: PrivateKeySigner = anvil.keys()[0].clone().into();
@@ -84,16 +98,6 @@ async fn setup_test_environment() -> anyhow::Result<(
 
     let verifier = MockVerifier::deploy(&provider, [0, 0, 0, 0].into()).await?;
 
-    let tm_client = Arc::new(HttpClient::new(CELESTIA_RPC_URL)?);
-    let trusted_block_hash = tm_client
-        .header(BATCH_START - 1)
-        .await?
-        .header
-        .hash()
-        .as_bytes()
-        .try_into()
-        .unwrap();
-
     // Deploy the contract.
     let implementation = IBlobstream::deploy(&provider).await?;
     tracing::debug!(target: "blobstream0::cli", "Deployed implementation contract");
@@ -107,6 +111,8 @@ async fn setup_test_environment() -> anyhow::Result<(
             _trustedHash: trusted_block_hash,
             _trustedHeight: BATCH_START as u64 - 1,
             _minBatchSize: 0,
+            _chainIdHash: chain_id_hash(&trusted_header.chain_id).into(),
+            _leafContent: LeafContent::DataHash.into(),
         }
         .abi_encode()
         .into(),
@@ -123,8 +129,12 @@ async fn e2e_basic_range() -> anyhow::Result<()> {
     let (_anvil, contract) = setup_test_environment().await?;
 
     let tm_client = Arc::new(HttpClient::new(CELESTIA_RPC_URL)?);
-    let receipt =
-        prove_block_range(tm_client.clone(), BATCH_START as u64..BATCH_END as u64).await?;
+    let receipt = prove_block_range(
+        tm_client.clone(),
+        BATCH_START as u64..BATCH_END as u64,
+        ProofConfig::default(),
+    )
+    .await?;
 
     post_batch(&contract, &receipt).await?;
 
@@ -178,6 +188,200 @@ async fn e2e_basic_range() -> anyhow::Result<()> {
         .await?;
     assert!(is_valid);
 
+    // Validate the locally generated proof matches the Celestia API.
+    let inclusion =
+        data_root_inclusion_proof(tm_client.clone(), &contract, PROOF_HEIGHT as u64, 0).await?;
+    assert_eq!(inclusion.commitment.proof_nonce, U256::from(1));
+    assert_eq!(inclusion.tuple.dataRoot, proof_data_root);
+    assert_eq!(inclusion.proof.key, U256::from(response.index));
+    assert_eq!(inclusion.proof.numLeaves, U256::from(response.total));
+    assert_eq!(
+        inclusion
+            .proof
+            .sideNodes
+            .iter()
+            .map(|node| node.to_vec())
+            .collect::<Vec<_>>(),
+        response.aunts
+    );
+    let is_valid = contract
+        .verifyAttestation(
+            inclusion.commitment.proof_nonce,
+            inclusion.tuple,
+            inclusion.proof,
+        )
+        .call()
+        .await?;
+    assert!(is_valid);
+
+    // Validate the header hash inclusion proof against the committed header hash root.
+    let inclusion =
+        header_hash_inclusion_proof(tm_client.clone(), &contract, PROOF_HEIGHT as u64, 0).await?;
+    let proof_header_hash = tm_client.header(PROOF_HEIGHT).await?.header.hash();
+    assert_eq!(
+        inclusion.header_hash.as_slice(),
+        proof_header_hash.as_bytes()
+    );
+    let is_valid = contract
+        .verifyHeaderHash(
+            inclusion.commitment.proof_nonce,
+            inclusion.header_hash,
+            inclusion.proof,
+        )
+        .call()
+        .await?;
+    assert!(is_valid);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_execute_range() -> anyhow::Result<()> {
+    let tm_client = Arc::new(HttpClient::new(CELESTIA_RPC_URL)?);
+    let report = execute_block_range(
+        tm_client,
+        BATCH_START as u64..BATCH_END as u64,
+        ProofConfig::default(),
+    )
+    .await?;
+
+    assert_eq!(report.commitment.newHeight, BATCH_END as u64 - 1);
+    assert_eq!(report.commitment.trustedHeight, BATCH_START as u64 - 1);
+    assert!(report.commitment.newHeaderTime > report.commitment.trustedHeaderTime);
+    assert!(report.segments > 0);
+    assert!(report.user_cycles > 0);
+    assert!(report.total_cycles >= report.user_cycles);
+    assert!(report.input_bytes > 0);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_decoded_revert_errors() -> anyhow::Result<()> {
+    let (anvil, contract) = setup_test_environment().await?;
+
+    let tm_client = Arc::new(HttpClient::new(CELESTIA_RPC_URL)?);
+    let receipt = prove_block_range(
+        tm_client.clone(),
+        BATCH_START as u64..BATCH_END as u64,
+        ProofConfig::default(),
+    )
+    .await?;
+    post_batch(&contract, &receipt).await?;
+
+    // Posting the same range again should revert with a decoded contract error.
+    let err = post_batch(&contract, &receipt).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ContractError>(),
+        Some(ContractError::Blobstream(
+            IBlobstreamErrors::InvalidTargetHeight(_)
+        ))
+    ));
+
+    // Admin calls from a non-owner should decode to the ownership error.
+    let other_signer: PrivateKeySigner = anvil.keys()[1].clone().into();
+    let other_provider = ProviderBuilder::new()
+        .wallet(EthereumWallet::from(other_signer))
+        .connect_http(anvil.endpoint().parse()?);
+    let err = IBlobstream::new(contract.address().clone(), other_provider)
+        .adminSetImageId([1u8; 32].into())
+        .send()
+        .await
+        .map_err(ContractError::from)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Blobstream(IBlobstreamErrors::OwnableUnauthorizedAccount(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_chain_id_mismatch() -> anyhow::Result<()> {
+    let (_anvil, contract) = setup_test_environment().await?;
+
+    let tm_client = Arc::new(HttpClient::new(CELESTIA_RPC_URL)?);
+    let receipt = prove_block_range(
+        tm_client.clone(),
+        BATCH_START as u64..BATCH_END as u64,
+        ProofConfig::default(),
+    )
+    .await?;
+
+    // Proofs of a different chain than the contract tracks should be rejected.
+    contract
+        .adminSetChainIdHash([3u8; 32].into())
+        .send()
+        .await?
+        .watch()
+        .await?;
+    let err = post_batch(&contract, &receipt).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ContractError>(),
+        Some(ContractError::Blobstream(
+            IBlobstreamErrors::InvalidChainIdHash(_)
+        ))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_leaf_content_mismatch() -> anyhow::Result<()> {
+    let (_anvil, contract) = setup_test_environment().await?;
+
+    // Decoding the inputs with another proto version should prove the same headers.
+    let tm_client = Arc::new(tendermint_client(CELESTIA_RPC_URL, ProtoVersion::V0_34)?);
+    let receipt = prove_block_range(
+        tm_client.clone(),
+        BATCH_START as u64..BATCH_END as u64,
+        ProofConfig {
+            proto_version: ProtoVersion::V0_34,
+            leaf_content: LeafContent::AppHash,
+        },
+    )
+    .await?;
+
+    // Proofs committing to another header field than the contract was initialized with should be
+    // rejected.
+    let err = post_batch(&contract, &receipt).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ContractError>(),
+        Some(ContractError::Blobstream(
+            IBlobstreamErrors::InvalidLeafContent(_)
+        ))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trusted_header_expired() -> anyhow::Result<()> {
+    let (_anvil, contract) = setup_test_environment().await?;
+
+    let tm_client = Arc::new(HttpClient::new(CELESTIA_RPC_URL)?);
+    let receipt = prove_block_range(
+        tm_client.clone(),
+        BATCH_START as u64..BATCH_END as u64,
+        ProofConfig::default(),
+    )
+    .await?;
+
+    // Move the chain past the trusting period of the trusted header.
+    let trusting_period = contract.TRUSTING_PERIOD().call().await?;
+    contract
+        .provider()
+        .raw_request::<_, serde_json::Value>("evm_increaseTime".into(), (trusting_period,))
+        .await?;
+    let err = post_batch(&contract, &receipt).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ContractError>(),
+        Some(ContractError::Blobstream(
+            IBlobstreamErrors::TrustedHeaderExpired(_)
+        ))
+    ));
+
     Ok(())
 }
 
@@ -256,8 +460,12 @@ async fn test_contract_upgrade() -> anyhow::Result<()> {
 
     // Test that the new implementation works as normal
     let tm_client = Arc::new(HttpClient::new(CELESTIA_RPC_URL)?);
-    let receipt =
-        prove_block_range(tm_client.clone(), BATCH_START as u64..BATCH_END as u64).await?;
+    let receipt = prove_block_range(
+        tm_client.clone(),
+        BATCH_START as u64..BATCH_END as u64,
+        ProofConfig::default(),
+    )
+    .await?;
 
     post_batch(&contract, &receipt).await?;
 
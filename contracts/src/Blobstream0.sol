@@ -22,12 +22,14 @@ import {IDAOracle} from "blobstream/IDAOracle.sol";
 import {UUPSUpgradeable} from "openzeppelin-upgradeable/contracts/proxy/utils/UUPSUpgradeable.sol";
 import {Ownable2StepUpgradeable} from "openzeppelin-upgradeable/contracts/access/Ownable2StepUpgradeable.sol";
 import {Initializable} from "openzeppelin-upgradeable/contracts/proxy/utils/Initializable.sol";
+import {MulticallUpgradeable} from "openzeppelin-upgradeable/contracts/utils/MulticallUpgradeable.sol";
 import "./RangeCommitment.sol";
 import "blobstream/DataRootTuple.sol";
 import "blobstream/lib/tree/binary/BinaryMerkleTree.sol";
 
 /// @title A starter application using RISC Zero.
-contract Blobstream0 is IDAOracle, Initializable, UUPSUpgradeable, Ownable2StepUpgradeable {
+/// @dev Multicall batches admin calls, such as the calls setting new state in `upgradeToAndCall`.
+contract Blobstream0 is IDAOracle, Initializable, UUPSUpgradeable, Ownable2StepUpgradeable, MulticallUpgradeable {
     /// @notice Data commitment stored for the block range [startBlock, endBlock) with proof nonce.
     /// NOTE: This event matches existing Blobstream contracts, for ease of integration.
     /// @param proofNonce The nonce of the proof.
@@ -50,12 +52,38 @@ contract Blobstream0 is IDAOracle, Initializable, UUPSUpgradeable, Ownable2StepU
     /// @param validatorBitmap The validator bitmap for the block range.
     event ValidatorBitmapEquivocation(uint64 trustedBlock, uint64 targetBlock, uint256 validatorBitmap);
 
+    /// @notice Times of the trusted and new headers of the range stored at the proof nonce, as
+    ///         committed by the proof. Allows indexers to expose block times without Tendermint RPC
+    ///         calls.
+    /// @param proofNonce The nonce of the proof.
+    /// @param trustedHeaderTime The unix time in seconds of the trusted header.
+    /// @param newHeaderTime The unix time in seconds of the new header.
+    event RangeHeaderTimes(uint256 proofNonce, uint64 trustedHeaderTime, uint64 newHeaderTime);
+
     /// @notice Target height for next batch was below the current height.
     error InvalidTargetHeight();
 
     /// @notice Trusted block hash does not equal the commitment from the new batch.
     error InvalidTrustedHeaderHash();
 
+    /// @notice Trusted height of the new batch does not equal the latest height.
+    error InvalidTrustedHeight();
+
+    /// @notice Trusted header of the new batch is older than the trusting period.
+    error TrustedHeaderExpired();
+
+    /// @notice Chain ID hash of the new batch does not equal the chain this contract tracks.
+    error InvalidChainIdHash();
+
+    /// @notice Leaf content of the new batch does not equal the one this contract was initialized
+    ///         with.
+    error InvalidLeafContent();
+
+    /// @notice Period after the time of the trusted header in which a new header can be proven from
+    ///         it, matching the trusting period of the light client guest. A proof posted exactly
+    ///         at the end of the period is accepted.
+    uint64 public constant TRUSTING_PERIOD = 2 weeks;
+
     /// @notice Minimum number of blocks required for a valid batch update. The batch size must be
     ///         larger than this value.
     /// @dev This is to ensure there is no DOS condition from doing single/small batch updates.
@@ -86,6 +114,22 @@ contract Blobstream0 is IDAOracle, Initializable, UUPSUpgradeable, Ownable2StepU
     /// @notice This is a mapping of proof nonces to merkle roots at those heights.
     mapping(uint256 => bytes32) merkleRoots;
 
+    /// @notice SHA-256 hash of the Tendermint chain ID that proofs must be verified against.
+    /// @dev declared after existing storage to keep the layout of upgraded deployments.
+    bytes32 public chainIdHash;
+
+    /// @notice This is a mapping of proof nonces to merkle roots of the header hashes of the range
+    ///         stored at that nonce. Any field of a header can be proven against its header hash.
+    /// @dev declared after existing storage to keep the layout of upgraded deployments.
+    mapping(uint256 => bytes32) public headerHashRoots;
+
+    /// @notice Header field committed as the root of each data root tuple: 0 for the data hash,
+    ///         1 for the app hash and 2 for the header hash. Celestia deployments use the data
+    ///         hash, which is the data root of the block.
+    /// @dev declared after existing storage to keep the layout of upgraded deployments, which
+    ///      default to the data hash.
+    uint8 public leafContent;
+
     /// @dev onlyOwner specified for authorization for an upgrade.
     /// @dev DO NOT REMOVE! It is mandatory for upgradability.
     function _authorizeUpgrade(address newImplementation) internal override onlyOwner {}
@@ -95,17 +139,22 @@ contract Blobstream0 is IDAOracle, Initializable, UUPSUpgradeable, Ownable2StepU
         IRiscZeroVerifier _verifier,
         bytes32 _trustedHash,
         uint64 _trustedHeight,
-        uint64 _minBatchSize
+        uint64 _minBatchSize,
+        bytes32 _chainIdHash,
+        uint8 _leafContent
     ) public initializer {
         __Ownable_init(_admin);
         __Ownable2Step_init();
         __UUPSUpgradeable_init();
+        __Multicall_init();
 
         verifier = _verifier;
         latestBlockHash = _trustedHash;
         latestHeight = _trustedHeight;
         imageId = ImageID.LIGHT_CLIENT_GUEST_ID;
         minBatchSize = _minBatchSize;
+        chainIdHash = _chainIdHash;
+        leafContent = _leafContent;
 
         // Proof nonce initialized as 1 to maintain compatibility with existing implementations and
         // avoid default value confusion.
@@ -128,6 +177,18 @@ contract Blobstream0 is IDAOracle, Initializable, UUPSUpgradeable, Ownable2StepU
         verifier = _verifier;
     }
 
+    /// @notice Only the admin can update. Updates the hash of the Tendermint chain ID to accept
+    ///         proofs for. Must be set after upgrading a deployment initialized without it.
+    function adminSetChainIdHash(bytes32 _chainIdHash) external onlyOwner {
+        chainIdHash = _chainIdHash;
+    }
+
+    /// @notice Only the admin can update. Updates the header field committed for each block. Must
+    ///         be set after upgrading a deployment that should not commit to the data hash.
+    function adminSetLeafContent(uint8 _leafContent) external onlyOwner {
+        leafContent = _leafContent;
+    }
+
     /// @notice Validate a proof of a new header range, update state.
     function updateRange(bytes calldata _commitBytes, bytes calldata _seal) external {
         RangeCommitment memory commit = abi.decode(_commitBytes, (RangeCommitment));
@@ -138,10 +199,24 @@ contract Blobstream0 is IDAOracle, Initializable, UUPSUpgradeable, Ownable2StepU
         if (commit.trustedHeaderHash != latestBlockHash) {
             revert InvalidTrustedHeaderHash();
         }
+        if (commit.trustedHeight != latestHeight) {
+            revert InvalidTrustedHeight();
+        }
+        if (commit.chainIdHash != chainIdHash) {
+            revert InvalidChainIdHash();
+        }
+        if (commit.leafContent != leafContent) {
+            revert InvalidLeafContent();
+        }
+        // Validators of an expired trusted header may no longer be bonded, so cannot be trusted.
+        if (block.timestamp > commit.trustedHeaderTime + TRUSTING_PERIOD) {
+            revert TrustedHeaderExpired();
+        }
         verifier.verify(_seal, imageId, sha256(_commitBytes));
 
         emit DataCommitmentStored(proofNonce, latestHeight, commit.newHeight, commit.merkleRoot);
         emit ValidatorBitmapEquivocation(latestHeight, commit.newHeight, commit.validatorBitmap);
+        emit RangeHeaderTimes(proofNonce, commit.trustedHeaderTime, commit.newHeaderTime);
 
         // Update latest block in state
         latestHeight = commit.newHeight;
@@ -151,6 +226,7 @@ contract Blobstream0 is IDAOracle, Initializable, UUPSUpgradeable, Ownable2StepU
         // Set merkle root to monotomically increasing nonce. This is kept as is for compatibility
         // with alternative versions.
         merkleRoots[proofNonce] = commit.merkleRoot;
+        headerHashRoots[proofNonce] = commit.headerHashRoot;
         proofNonce++;
     }
 
@@ -175,4 +251,26 @@ contract Blobstream0 is IDAOracle, Initializable, UUPSUpgradeable, Ownable2StepU
 
         return isProofValid;
     }
+
+    /// @notice Verify a header hash is of a height in the range stored at the proof nonce. The
+    ///         height is the start block of the range plus one plus the key of the proof.
+    /// @param _proofNonce Nonce of the header hash root to prove against.
+    /// @param _headerHash Header hash to prove inclusion of.
+    /// @param _proof Binary Merkle tree proof that `_headerHash` is in the root at `_proofNonce`.
+    /// @return `true` is proof is valid, `false` otherwise.
+    function verifyHeaderHash(uint256 _proofNonce, bytes32 _headerHash, BinaryMerkleProof memory _proof)
+        external
+        view
+        returns (bool)
+    {
+        if (_proofNonce == 0 || _proofNonce >= proofNonce) {
+            return false;
+        }
+
+        bytes32 root = headerHashRoots[_proofNonce];
+
+        (bool isProofValid,) = BinaryMerkleTree.verify(root, _proof, abi.encodePacked(_headerHash));
+
+        return isProofValid;
+    }
 }
@@ -25,4 +25,10 @@ struct RangeCommitment {
     bytes32 newHeaderHash;
     bytes32 merkleRoot;
     uint256 validatorBitmap;
+    bytes32 chainIdHash;
+    uint64 trustedHeight;
+    uint64 trustedHeaderTime;
+    uint64 newHeaderTime;
+    bytes32 headerHashRoot;
+    uint8 leafContent;
 }
@@ -14,15 +14,252 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-pragma solidity ^0.8.20;
+pragma solidity ^0.8.24;
 
+import {Test} from "forge-std/Test.sol";
 import {RiscZeroMockVerifier} from "risc0/test/RiscZeroMockVerifier.sol";
 import {RiscZeroGroth16Verifier} from "risc0/groth16/RiscZeroGroth16Verifier.sol";
 import {ERC1967Proxy} from "openzeppelin/contracts/proxy/ERC1967/ERC1967Proxy.sol";
+import {OwnableUpgradeable} from "openzeppelin-upgradeable/contracts/access/OwnableUpgradeable.sol";
+import {Blobstream0} from "../src/Blobstream0.sol";
+import {BinaryMerkleProof} from "blobstream/lib/tree/binary/BinaryMerkleProof.sol";
+import "../src/RangeCommitment.sol";
 
-contract Blobstream0Test {
-    // NOTE: This is only used to build these contracts to be used for the CLI and tests, ignore
-    RiscZeroGroth16Verifier a;
-    RiscZeroMockVerifier b;
-    ERC1967Proxy c;
+contract Blobstream0Test is Test {
+    bytes32 constant TRUSTED_HASH = bytes32(uint256(1));
+    uint64 constant TRUSTED_HEIGHT = 10;
+    uint64 constant MIN_BATCH_SIZE = 4;
+    bytes32 constant CHAIN_ID_HASH = sha256("private");
+    /// @dev Leaf content of the data hash, which Celestia deployments use.
+    uint8 constant LEAF_CONTENT = 0;
+
+    // NOTE: This is only used to build the verifier to be used for the CLI.
+    RiscZeroGroth16Verifier groth16Verifier;
+
+    RiscZeroMockVerifier verifier;
+    Blobstream0 blobstream;
+    address admin;
+
+    function setUp() public {
+        admin = makeAddr("admin");
+        vm.warp(1_700_000_000);
+        verifier = new RiscZeroMockVerifier(bytes4(0));
+        Blobstream0 implementation = new Blobstream0();
+        bytes memory initData = abi.encodeCall(
+            Blobstream0.initialize,
+            (admin, verifier, TRUSTED_HASH, TRUSTED_HEIGHT, MIN_BATCH_SIZE, CHAIN_ID_HASH, LEAF_CONTENT)
+        );
+        blobstream = Blobstream0(address(new ERC1967Proxy(address(implementation), initData)));
+    }
+
+    /// @dev Commitment of a valid range from the latest state of the contract.
+    function validCommitment() internal view returns (RangeCommitment memory) {
+        return RangeCommitment({
+            trustedHeaderHash: blobstream.latestBlockHash(),
+            newHeight: blobstream.latestHeight() + MIN_BATCH_SIZE + 1,
+            newHeaderHash: bytes32(uint256(2)),
+            merkleRoot: bytes32(uint256(3)),
+            validatorBitmap: 1,
+            chainIdHash: CHAIN_ID_HASH,
+            trustedHeight: blobstream.latestHeight(),
+            trustedHeaderTime: uint64(block.timestamp) - 1 hours,
+            newHeaderTime: uint64(block.timestamp),
+            headerHashRoot: bytes32(uint256(4)),
+            leafContent: LEAF_CONTENT
+        });
+    }
+
+    /// @dev Journal and mock seal of a proof committing to `commit`.
+    function prove(RangeCommitment memory commit) internal view returns (bytes memory journal, bytes memory seal) {
+        journal = abi.encode(commit);
+        seal = verifier.mockProve(blobstream.imageId(), sha256(journal)).seal;
+    }
+
+    function testUpdateRange() public {
+        RangeCommitment memory commit = validCommitment();
+        (bytes memory journal, bytes memory seal) = prove(commit);
+        blobstream.updateRange(journal, seal);
+
+        assertEq(blobstream.latestHeight(), commit.newHeight);
+        assertEq(blobstream.latestBlockHash(), commit.newHeaderHash);
+        assertEq(blobstream.proofNonce(), 2);
+    }
+
+    function testInvalidChainIdHash() public {
+        RangeCommitment memory commit = validCommitment();
+        commit.chainIdHash = sha256("other");
+        (bytes memory journal, bytes memory seal) = prove(commit);
+
+        vm.expectRevert(Blobstream0.InvalidChainIdHash.selector);
+        blobstream.updateRange(journal, seal);
+    }
+
+    function testAdminSetChainIdHash() public {
+        bytes32 otherChainIdHash = sha256("other");
+        vm.prank(admin);
+        blobstream.adminSetChainIdHash(otherChainIdHash);
+        assertEq(blobstream.chainIdHash(), otherChainIdHash);
+
+        // Proofs of the previous chain are rejected after the change.
+        (bytes memory journal, bytes memory seal) = prove(validCommitment());
+        vm.expectRevert(Blobstream0.InvalidChainIdHash.selector);
+        blobstream.updateRange(journal, seal);
+
+        RangeCommitment memory commit = validCommitment();
+        commit.chainIdHash = otherChainIdHash;
+        (journal, seal) = prove(commit);
+        blobstream.updateRange(journal, seal);
+        assertEq(blobstream.latestHeight(), commit.newHeight);
+    }
+
+    function testAdminSetChainIdHashOnlyOwner() public {
+        address other = makeAddr("other");
+        vm.expectRevert(abi.encodeWithSelector(OwnableUpgradeable.OwnableUnauthorizedAccount.selector, other));
+        vm.prank(other);
+        blobstream.adminSetChainIdHash(sha256("other"));
+
+        assertEq(blobstream.chainIdHash(), CHAIN_ID_HASH);
+    }
+
+    function testUpgradeToAndCallMulticall() public {
+        Blobstream0 newImplementation = new Blobstream0();
+        bytes32 newImageId = bytes32(uint256(5));
+        bytes32 otherChainIdHash = sha256("other");
+        bytes[] memory calls = new bytes[](2);
+        calls[0] = abi.encodeCall(Blobstream0.adminSetImageId, (newImageId));
+        calls[1] = abi.encodeCall(Blobstream0.adminSetChainIdHash, (otherChainIdHash));
+
+        vm.prank(admin);
+        blobstream.upgradeToAndCall(address(newImplementation), abi.encodeCall(Blobstream0.multicall, (calls)));
+
+        assertEq(blobstream.imageId(), newImageId);
+        assertEq(blobstream.chainIdHash(), otherChainIdHash);
+    }
+
+    function testMulticallOnlyOwner() public {
+        bytes[] memory calls = new bytes[](1);
+        calls[0] = abi.encodeCall(Blobstream0.adminSetChainIdHash, (sha256("other")));
+
+        address other = makeAddr("other");
+        vm.expectRevert(abi.encodeWithSelector(OwnableUpgradeable.OwnableUnauthorizedAccount.selector, other));
+        vm.prank(other);
+        blobstream.multicall(calls);
+
+        assertEq(blobstream.chainIdHash(), CHAIN_ID_HASH);
+    }
+
+    function testInvalidTrustedHeight() public {
+        RangeCommitment memory commit = validCommitment();
+        // The trusted header hash matches, but is committed at another height.
+        commit.trustedHeight = TRUSTED_HEIGHT - 1;
+        (bytes memory journal, bytes memory seal) = prove(commit);
+
+        vm.expectRevert(Blobstream0.InvalidTrustedHeight.selector);
+        blobstream.updateRange(journal, seal);
+    }
+
+    function testTrustedHeaderAtTrustingPeriod() public {
+        RangeCommitment memory commit = validCommitment();
+        commit.trustedHeaderTime = uint64(block.timestamp) - blobstream.TRUSTING_PERIOD();
+        (bytes memory journal, bytes memory seal) = prove(commit);
+        blobstream.updateRange(journal, seal);
+
+        assertEq(blobstream.latestHeight(), commit.newHeight);
+    }
+
+    function testTrustedHeaderExpired() public {
+        RangeCommitment memory commit = validCommitment();
+        commit.trustedHeaderTime = uint64(block.timestamp) - blobstream.TRUSTING_PERIOD() - 1;
+        (bytes memory journal, bytes memory seal) = prove(commit);
+
+        vm.expectRevert(Blobstream0.TrustedHeaderExpired.selector);
+        blobstream.updateRange(journal, seal);
+    }
+
+    function testRangeHeaderTimes() public {
+        RangeCommitment memory commit = validCommitment();
+        (bytes memory journal, bytes memory seal) = prove(commit);
+
+        vm.expectEmit(address(blobstream));
+        emit Blobstream0.RangeHeaderTimes(1, commit.trustedHeaderTime, commit.newHeaderTime);
+        blobstream.updateRange(journal, seal);
+    }
+
+    /// @dev Root of the header hashes 1 to 5 and the proofs of the hashes 3 and 5, as built by
+    ///      `header_hash_inclusion_proof` in the primitives crate, which tests the same values.
+    bytes32 constant HEADER_HASH_ROOT = 0xc48c0df7d9b37592c69ba5ca2afc8ada511550e607e6dfe7fdef6b85d89f5269;
+
+    function headerHashProof(uint256 key) internal pure returns (BinaryMerkleProof memory proof) {
+        proof.key = key;
+        proof.numLeaves = 5;
+        if (key == 2) {
+            proof.sideNodes = new bytes32[](3);
+            proof.sideNodes[0] = 0x82f02cf2ac0074619e6d747c35e08b29431a16943ddf81cfd9065c004ee6364a;
+            proof.sideNodes[1] = 0x0971c8a1ce81287ccbc95aa4f171a5f807fb13ea2118f56b99769459a64906ad;
+            proof.sideNodes[2] = 0x086fb60bd968fe68ecec6a8d826ea5aa7d3d8020e644d7c5d0e07ded456ca3e8;
+        } else {
+            proof.sideNodes = new bytes32[](1);
+            proof.sideNodes[0] = 0x45f385494f9f6116ec5530e7e9e24e2fdf6388c47d72e29657ce7b860c1484c3;
+        }
+    }
+
+    function testVerifyHeaderHash() public {
+        RangeCommitment memory commit = validCommitment();
+        commit.headerHashRoot = HEADER_HASH_ROOT;
+        (bytes memory journal, bytes memory seal) = prove(commit);
+        blobstream.updateRange(journal, seal);
+        assertEq(blobstream.headerHashRoots(1), HEADER_HASH_ROOT);
+
+        assertTrue(blobstream.verifyHeaderHash(1, bytes32(uint256(3)), headerHashProof(2)));
+        assertTrue(blobstream.verifyHeaderHash(1, bytes32(uint256(5)), headerHashProof(4)));
+
+        // Wrong header hash or index.
+        assertFalse(blobstream.verifyHeaderHash(1, bytes32(uint256(4)), headerHashProof(2)));
+        BinaryMerkleProof memory wrongIndex = headerHashProof(2);
+        wrongIndex.key = 3;
+        assertFalse(blobstream.verifyHeaderHash(1, bytes32(uint256(3)), wrongIndex));
+
+        // Nonces without a root, or with a different root.
+        assertFalse(blobstream.verifyHeaderHash(0, bytes32(uint256(3)), headerHashProof(2)));
+        assertFalse(blobstream.verifyHeaderHash(2, bytes32(uint256(3)), headerHashProof(2)));
+        (journal, seal) = prove(validCommitment());
+        blobstream.updateRange(journal, seal);
+        assertFalse(blobstream.verifyHeaderHash(2, bytes32(uint256(3)), headerHashProof(2)));
+    }
+
+    function testInvalidLeafContent() public {
+        RangeCommitment memory commit = validCommitment();
+        // Leaf content of the app hash, which this contract was not initialized with.
+        commit.leafContent = 1;
+        (bytes memory journal, bytes memory seal) = prove(commit);
+
+        vm.expectRevert(Blobstream0.InvalidLeafContent.selector);
+        blobstream.updateRange(journal, seal);
+    }
+
+    function testAdminSetLeafContent() public {
+        vm.prank(admin);
+        blobstream.adminSetLeafContent(1);
+        assertEq(blobstream.leafContent(), 1);
+
+        // Proofs of the previous leaf content are rejected after the change.
+        (bytes memory journal, bytes memory seal) = prove(validCommitment());
+        vm.expectRevert(Blobstream0.InvalidLeafContent.selector);
+        blobstream.updateRange(journal, seal);
+
+        RangeCommitment memory commit = validCommitment();
+        commit.leafContent = 1;
+        (journal, seal) = prove(commit);
+        blobstream.updateRange(journal, seal);
+        assertEq(blobstream.latestHeight(), commit.newHeight);
+    }
+
+    function testAdminSetLeafContentOnlyOwner() public {
+        address other = makeAddr("other");
+        vm.expectRevert(abi.encodeWithSelector(OwnableUpgradeable.OwnableUnauthorizedAccount.selector, other));
+        vm.prank(other);
+        blobstream.adminSetLeafContent(1);
+
+        assertEq(blobstream.leafContent(), LEAF_CONTENT);
+    }
 }
@@ -0,0 +1,112 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use blobstream0_primitives::proto::ProtoVersion;
+use tendermint_rpc::{client::CompatMode, HttpClient};
+
+/// RPC dialect of the CometBFT version. The RPC of 0.38 is compatible with 0.37.
+fn compat_mode(version: ProtoVersion) -> CompatMode {
+    match version {
+        ProtoVersion::V0_34 => CompatMode::V0_34,
+        ProtoVersion::V0_37 | ProtoVersion::V0_38 => CompatMode::V0_37,
+    }
+}
+
+/// Creates a Tendermint RPC client for a chain running the CometBFT version of `proto_version`.
+/// Chains on 0.34 have no `header` endpoint, so headers are fetched through `block` instead.
+pub fn tendermint_client(url: &str, proto_version: ProtoVersion) -> anyhow::Result<HttpClient> {
+    Ok(HttpClient::builder(url.parse()?)
+        .compat_mode(compat_mode(proto_version))
+        .build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use tendermint_rpc::Client;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+
+    use super::*;
+
+    /// Response of a CometBFT 0.34 node to a `block` request for height 10, from the RPC fixtures of
+    /// tendermint-rs.
+    const BLOCK_V0_34: &str = include_str!("../testdata/block_v0_34.json");
+
+    /// Response of a CometBFT 0.34 node to methods added in later versions.
+    const METHOD_NOT_FOUND: &str = r#"{"jsonrpc":"2.0","id":"","error":{"code":-32601,"message":"Method not found","data":""}}"#;
+
+    /// Reads a JSON-RPC request and responds as a CometBFT 0.34 node would.
+    async fn respond_v0_34(mut stream: TcpStream) -> anyhow::Result<()> {
+        let mut request = Vec::new();
+        let mut buf = [0; 4096];
+        let body_len = loop {
+            let n = stream.read(&mut buf).await?;
+            anyhow::ensure!(n > 0, "connection closed before the request was read");
+            request.extend_from_slice(&buf[..n]);
+            let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") else {
+                continue;
+            };
+            let headers = String::from_utf8_lossy(&request[..end]).to_lowercase();
+            let content_length = headers
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .map_or(Ok(0), |len| len.trim().parse::<usize>())?;
+            if request.len() >= end + 4 + content_length {
+                break content_length;
+            }
+        };
+        let body: serde_json::Value = serde_json::from_slice(&request[request.len() - body_len..])?;
+
+        let response = match body["method"].as_str() {
+            Some("block") => BLOCK_V0_34,
+            _ => METHOD_NOT_FOUND,
+        };
+        let http = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            response.len(),
+            response
+        );
+        stream.write_all(http.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Serves requests as a CometBFT 0.34 node, returning its URL.
+    async fn serve_v0_34() -> anyhow::Result<String> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(respond_v0_34(stream));
+            }
+        });
+        Ok(url)
+    }
+
+    #[tokio::test]
+    async fn header_from_v0_34_node() -> anyhow::Result<()> {
+        let url = serve_v0_34().await?;
+
+        let client = tendermint_client(&url, ProtoVersion::V0_34)?;
+        let header = client.header(10u32).await?.header;
+        assert_eq!(header.height.value(), 10);
+        assert_eq!(header.chain_id.as_str(), "dockerchain");
+
+        // The default dialect requests the `header` endpoint, which 0.34 nodes do not have.
+        let client = tendermint_client(&url, ProtoVersion::V0_37)?;
+        assert!(client.header(10u32).await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Typed decoding of errors returned from transactions and calls to the Blobstream0 contract.
+
+use alloy::{
+    contract::Error as AlloyContractError, primitives::Bytes, providers::PendingTransactionError,
+    sol_types::SolInterface,
+};
+use blobstream0_primitives::IBlobstream::IBlobstreamErrors;
+
+alloy::sol! {
+    /// Custom errors that can be raised by the RISC Zero verifier contracts when `updateRange`
+    /// verifies a seal. Covers the Groth16 and mock verifiers, as well as the verifier router and
+    /// emergency stop wrappers.
+    #[derive(Debug)]
+    interface IRiscZeroVerifier {
+        error VerificationFailed();
+        error SelectorMismatch(bytes4 received, bytes4 expected);
+        error SelectorUnknown(bytes4 selector);
+        error SelectorRemoved(bytes4 selector);
+        error EnforcedPause();
+    }
+}
+pub use IRiscZeroVerifier::IRiscZeroVerifierErrors;
+
+/// Error from sending a transaction or making a call to the Blobstream0 contract, with the revert
+/// reason decoded when it matches a known contract error.
+#[derive(Debug, thiserror::Error)]
+pub enum ContractError {
+    /// Reverted with a custom error from the Blobstream0 contract. This includes the ownership and
+    /// upgrade errors inherited from the OpenZeppelin contracts.
+    #[error("Blobstream0 contract reverted: {}", describe_blobstream_error(.0))]
+    Blobstream(IBlobstreamErrors),
+    /// Reverted with a custom error from the RISC Zero verifier while verifying the seal.
+    #[error("RISC Zero verifier reverted: {}", describe_verifier_error(.0))]
+    Verifier(IRiscZeroVerifierErrors),
+    /// Reverted with data that does not match any known contract error.
+    #[error("contract reverted with unknown data: {0}")]
+    UnknownRevert(Bytes),
+    /// Error that did not include any revert data, such as a transport error.
+    #[error(transparent)]
+    Contract(AlloyContractError),
+    /// Error while waiting for the transaction to be included.
+    #[error(transparent)]
+    PendingTransaction(#[from] PendingTransactionError),
+}
+
+impl ContractError {
+    /// Decodes revert data returned from the contract into the matching error.
+    pub fn from_revert_data(data: Bytes) -> Self {
+        if let Ok(err) = IBlobstreamErrors::abi_decode(&data) {
+            return Self::Blobstream(err);
+        }
+        if let Ok(err) = IRiscZeroVerifierErrors::abi_decode(&data) {
+            return Self::Verifier(err);
+        }
+        Self::UnknownRevert(data)
+    }
+}
+
+impl From<AlloyContractError> for ContractError {
+    fn from(err: AlloyContractError) -> Self {
+        match err.as_revert_data() {
+            Some(data) => Self::from_revert_data(data),
+            None => Self::Contract(err),
+        }
+    }
+}
+
+fn describe_blobstream_error(err: &IBlobstreamErrors) -> String {
+    match err {
+        IBlobstreamErrors::InvalidTargetHeight(_) => {
+            "InvalidTargetHeight, new height must be greater than the latest height plus the \
+             minimum batch size"
+                .to_string()
+        }
+        IBlobstreamErrors::InvalidTrustedHeaderHash(_) => {
+            "InvalidTrustedHeaderHash, trusted header hash of the proof does not match the latest \
+             block hash of the contract"
+                .to_string()
+        }
+        IBlobstreamErrors::InvalidTrustedHeight(_) => {
+            "InvalidTrustedHeight, trusted height of the proof does not match the latest height \
+             of the contract"
+                .to_string()
+        }
+        IBlobstreamErrors::InvalidLeafContent(_) => {
+            "InvalidLeafContent, leaf content of the proof does not match the leaf content of the \
+             contract"
+                .to_string()
+        }
+        IBlobstreamErrors::OwnableUnauthorizedAccount(e) => {
+            format!(
+                "OwnableUnauthorizedAccount, {} is not the contract owner",
+                e.account
+            )
+        }
+        IBlobstreamErrors::OwnableInvalidOwner(e) => {
+            format!("OwnableInvalidOwner, {} is not a valid owner", e.owner)
+        }
+        other => format!("{:?}", other),
+    }
+}
+
+fn describe_verifier_error(err: &IRiscZeroVerifierErrors) -> String {
+    match err {
+        IRiscZeroVerifierErrors::VerificationFailed(_) => {
+            "VerificationFailed, seal is not valid for the image ID and journal".to_string()
+        }
+        IRiscZeroVerifierErrors::SelectorMismatch(e) => format!(
+            "SelectorMismatch, seal selector {} does not match verifier selector {}",
+            e.received, e.expected
+        ),
+        other => format!("{:?}", other),
+    }
+}
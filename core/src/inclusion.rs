@@ -0,0 +1,244 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of data root inclusion proofs for heights committed to the Blobstream0 contract.
+
+use std::sync::Arc;
+
+use alloy::{
+    network::Network,
+    primitives::{B256, U256},
+    providers::Provider,
+};
+use anyhow::Context;
+use blobstream0_primitives::{
+    IBlobstream::{BinaryMerkleProof, DataCommitmentStored, DataRootTuple, IBlobstreamInstance},
+    LeafContent,
+};
+use tendermint_rpc::HttpClient;
+use tracing::{instrument, Level};
+
+use crate::{fetch_headers, ContractError};
+
+/// Number of blocks to query events for in each request, to stay within the limits of common RPC
+/// providers.
+pub(crate) const LOG_QUERY_BLOCK_RANGE: u64 = 10_000;
+
+/// Data commitment stored in the contract for a proven range of heights.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataCommitment {
+    /// Nonce the commitment is stored at, which is passed to `verifyAttestation`.
+    pub proof_nonce: U256,
+    /// Trusted height of the range. The data root of this height is not included.
+    pub start_block: u64,
+    /// Last height included in the commitment.
+    pub end_block: u64,
+    /// Merkle root of the data root tuples of the range.
+    pub data_commitment: B256,
+}
+
+impl DataCommitment {
+    /// Returns true if the data root of `height` is included in the commitment.
+    pub fn contains(&self, height: u64) -> bool {
+        self.start_block < height && height <= self.end_block
+    }
+}
+
+impl From<&DataCommitmentStored> for DataCommitment {
+    fn from(event: &DataCommitmentStored) -> Self {
+        Self {
+            proof_nonce: event.proofNonce,
+            start_block: event.startBlock,
+            end_block: event.endBlock,
+            data_commitment: event.dataCommitment,
+        }
+    }
+}
+
+/// Data root tuple of a height with the proof of its inclusion in a data commitment. These are the
+/// arguments to `verifyAttestation`.
+#[derive(Debug, Clone)]
+pub struct DataRootInclusionProof {
+    pub commitment: DataCommitment,
+    pub tuple: DataRootTuple,
+    pub proof: BinaryMerkleProof,
+}
+
+/// Header hash of a height with the proof of its inclusion in the header hash root stored with a
+/// data commitment. These are the arguments to `verifyHeaderHash`.
+#[derive(Debug, Clone)]
+pub struct HeaderHashInclusionProof {
+    pub commitment: DataCommitment,
+    pub header_hash: B256,
+    pub proof: BinaryMerkleProof,
+}
+
+/// Finds the latest data commitment that includes `height`, by scanning the `DataCommitmentStored`
+/// events of the contract backwards from the latest block to `from_block`, which should be the
+/// block the contract was deployed in.
+#[instrument(target = "blobstream0::core", skip(contract), err, level = Level::DEBUG)]
+pub async fn find_data_commitment<P, N>(
+    contract: &IBlobstreamInstance<P, N>,
+    height: u64,
+    from_block: u64,
+) -> anyhow::Result<Option<DataCommitment>>
+where
+    P: Provider<N>,
+    N: Network,
+{
+    let mut to_block = contract.provider().get_block_number().await?;
+    while to_block >= from_block {
+        let start_block = to_block
+            .saturating_sub(LOG_QUERY_BLOCK_RANGE - 1)
+            .max(from_block);
+        tracing::debug!(
+            target: "blobstream0::core",
+            "Querying data commitments in blocks {}-{}",
+            start_block,
+            to_block
+        );
+        let events = contract
+            .DataCommitmentStored_filter()
+            .from_block(start_block)
+            .to_block(to_block)
+            .query()
+            .await
+            .map_err(ContractError::from)?;
+
+        // Commitments are not necessarily stored in order of height, as the trusted state can be
+        // reset by the admin, so the search continues to `from_block` even past commitments
+        // ending below the height.
+        if let Some((event, _)) = events
+            .iter()
+            .rev()
+            .find(|(event, _)| DataCommitment::from(event).contains(height))
+        {
+            return Ok(Some(DataCommitment::from(event)));
+        }
+
+        if start_block == 0 {
+            break;
+        }
+        to_block = start_block - 1;
+    }
+
+    Ok(None)
+}
+
+/// Generates the inclusion proof of the data root of `height` in the data commitment that
+/// includes it. The data commitment is found from contract events starting at `from_block`, and
+/// the merkle tree is rebuilt from the headers of the committed range with the leaf content of the
+/// contract.
+#[instrument(target = "blobstream0::core", skip(client, contract), err, level = Level::INFO)]
+pub async fn data_root_inclusion_proof<P, N>(
+    client: Arc<HttpClient>,
+    contract: &IBlobstreamInstance<P, N>,
+    height: u64,
+    from_block: u64,
+) -> anyhow::Result<DataRootInclusionProof>
+where
+    P: Provider<N>,
+    N: Network,
+{
+    let latest_height = contract.latestHeight().call().await?;
+    anyhow::ensure!(
+        height <= latest_height,
+        "height {} is above the latest height of the contract {}",
+        height,
+        latest_height
+    );
+    let leaf_content = LeafContent::try_from(contract.leafContent().call().await?)?;
+
+    let commitment = find_data_commitment(contract, height, from_block)
+        .await?
+        .with_context(|| {
+            format!(
+                "no data commitment including height {} found since block {}",
+                height, from_block
+            )
+        })?;
+
+    let headers =
+        fetch_headers(client, commitment.start_block + 1..commitment.end_block + 1).await?;
+    let (root, tuple, proof) =
+        blobstream0_primitives::data_root_inclusion_proof(&headers, height, leaf_content)
+            .context("height not found in fetched headers")?;
+    anyhow::ensure!(
+        commitment.data_commitment == root,
+        "merkle root of fetched headers {} does not match data commitment {} at nonce {}",
+        B256::from(root),
+        commitment.data_commitment,
+        commitment.proof_nonce
+    );
+
+    Ok(DataRootInclusionProof {
+        commitment,
+        tuple,
+        proof,
+    })
+}
+
+/// Generates the inclusion proof of the header hash of `height` in the header hash root stored with
+/// the data commitment that includes it. The data commitment is found from contract events
+/// starting at `from_block`, and the merkle tree is rebuilt from the headers of the committed
+/// range.
+#[instrument(target = "blobstream0::core", skip(client, contract), err, level = Level::INFO)]
+pub async fn header_hash_inclusion_proof<P, N>(
+    client: Arc<HttpClient>,
+    contract: &IBlobstreamInstance<P, N>,
+    height: u64,
+    from_block: u64,
+) -> anyhow::Result<HeaderHashInclusionProof>
+where
+    P: Provider<N>,
+    N: Network,
+{
+    let commitment = find_data_commitment(contract, height, from_block)
+        .await?
+        .with_context(|| {
+            format!(
+                "no data commitment including height {} found since block {}",
+                height, from_block
+            )
+        })?;
+    let header_hash_root = contract
+        .headerHashRoots(commitment.proof_nonce)
+        .call()
+        .await?;
+    anyhow::ensure!(
+        header_hash_root != B256::ZERO,
+        "no header hash root stored at nonce {}, which was stored before header hash roots were \
+         committed",
+        commitment.proof_nonce
+    );
+
+    let headers =
+        fetch_headers(client, commitment.start_block + 1..commitment.end_block + 1).await?;
+    let (root, header_hash, proof) =
+        blobstream0_primitives::header_hash_inclusion_proof(&headers, height)
+            .context("height not found in fetched headers")?;
+    anyhow::ensure!(
+        header_hash_root == root,
+        "header hash root of fetched headers {} does not match the root {} at nonce {}",
+        B256::from(root),
+        header_hash_root,
+        commitment.proof_nonce
+    );
+
+    Ok(HeaderHashInclusionProof {
+        commitment,
+        header_hash: header_hash.into(),
+        proof,
+    })
+}
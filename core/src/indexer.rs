@@ -0,0 +1,632 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Indexer of the data commitments stored by the Blobstream0 contract.
+//!
+//! The `DataCommitmentStored`, `ValidatorBitmapEquivocation`, `RangeHeaderTimes` and `HeadUpdate`
+//! events emitted by each `updateRange` call are combined into one record per proof nonce, and stored in an embedded
+//! database so that the index persists across restarts. The hash of the last indexed block is
+//! stored with the index, and the index is rewound to the last record still in the canonical chain
+//! when it no longer matches.
+
+use std::{future::Future, path::Path, time::Duration};
+
+use alloy::{
+    network::{primitives::HeaderResponse, BlockResponse, Network},
+    primitives::{B256, U256},
+    providers::Provider,
+    rpc::types::{Filter, Log},
+    sol_types::{SolEvent, SolValue},
+};
+use anyhow::Context;
+use blobstream0_primitives::IBlobstream::{
+    DataCommitmentStored, HeadUpdate, IBlobstreamInstance, RangeHeaderTimes,
+    ValidatorBitmapEquivocation,
+};
+use redb::{Database, ReadableTable, TableDefinition};
+use tracing::{instrument, Level};
+
+use crate::{inclusion::LOG_QUERY_BLOCK_RANGE, DataCommitment};
+
+/// Encoded [CommitmentRecord] for each proof nonce.
+const COMMITMENTS: TableDefinition<u64, &[u8]> = TableDefinition::new("commitments");
+
+/// Times of the trusted and end headers for each proof nonce. Kept apart from [COMMITMENTS], as
+/// contracts deployed before the times were committed do not emit them.
+const HEADER_TIMES: TableDefinition<u64, (u64, u64)> = TableDefinition::new("header_times");
+
+/// Proof nonces by the end height of each commitment, keyed by both as the trusted state can be
+/// reset by the admin to prove a range ending at the same height again.
+const END_HEIGHTS: TableDefinition<(u64, u64), ()> = TableDefinition::new("end_heights");
+
+/// Number and hash of the last Ethereum block indexed, under [CURSOR_KEY].
+const META: TableDefinition<&str, (u64, [u8; 32])> = TableDefinition::new("meta");
+const CURSOR_KEY: &str = "cursor";
+
+alloy::sol! {
+    /// Encoding of an [IndexedCommitment] in the database.
+    struct CommitmentRecord {
+        uint256 proofNonce;
+        uint64 startBlock;
+        uint64 endBlock;
+        bytes32 dataCommitment;
+        bytes32 headerHash;
+        uint256 validatorBitmap;
+        uint64 ethBlockNumber;
+        bytes32 ethBlockHash;
+        bytes32 transactionHash;
+    }
+}
+
+/// Data commitment with the details of the `updateRange` transaction that stored it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedCommitment {
+    pub commitment: DataCommitment,
+    /// Header hash of the end height of the commitment.
+    pub header_hash: B256,
+    /// Bitmap of the validators that signed both the trusted and end headers.
+    pub validator_bitmap: U256,
+    /// Unix time in seconds of the trusted header of the range, if emitted by the contract.
+    pub trusted_header_time: Option<u64>,
+    /// Unix time in seconds of the end header of the commitment, if emitted by the contract.
+    pub header_time: Option<u64>,
+    pub eth_block_number: u64,
+    pub eth_block_hash: B256,
+    pub transaction_hash: B256,
+}
+
+impl From<CommitmentRecord> for IndexedCommitment {
+    fn from(record: CommitmentRecord) -> Self {
+        Self {
+            commitment: DataCommitment {
+                proof_nonce: record.proofNonce,
+                start_block: record.startBlock,
+                end_block: record.endBlock,
+                data_commitment: record.dataCommitment,
+            },
+            header_hash: record.headerHash,
+            validator_bitmap: record.validatorBitmap,
+            trusted_header_time: None,
+            header_time: None,
+            eth_block_number: record.ethBlockNumber,
+            eth_block_hash: record.ethBlockHash,
+            transaction_hash: record.transactionHash,
+        }
+    }
+}
+
+impl From<&IndexedCommitment> for CommitmentRecord {
+    fn from(indexed: &IndexedCommitment) -> Self {
+        Self {
+            proofNonce: indexed.commitment.proof_nonce,
+            startBlock: indexed.commitment.start_block,
+            endBlock: indexed.commitment.end_block,
+            dataCommitment: indexed.commitment.data_commitment,
+            headerHash: indexed.header_hash,
+            validatorBitmap: indexed.validator_bitmap,
+            ethBlockNumber: indexed.eth_block_number,
+            ethBlockHash: indexed.eth_block_hash,
+            transactionHash: indexed.transaction_hash,
+        }
+    }
+}
+
+impl IndexedCommitment {
+    /// Proof nonce as the key of the database.
+    fn nonce_key(&self) -> anyhow::Result<u64> {
+        u64::try_from(self.commitment.proof_nonce).context("proof nonce exceeds 64 bits")
+    }
+}
+
+/// Index of the data commitments of a Blobstream0 contract, stored in a local database.
+pub struct Indexer {
+    db: Database,
+    from_block: u64,
+    confirmations: u64,
+}
+
+impl Indexer {
+    /// Opens or creates the index database at `path`. Indexing starts from `from_block`, which
+    /// should be the block the contract was deployed in, if the database is empty.
+    pub fn open(path: impl AsRef<Path>, from_block: u64) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let db = Database::create(path)
+            .with_context(|| format!("failed to open index database {}", path.display()))?;
+
+        // Create the tables, so that reads do not fail before the first sync.
+        let txn = db.begin_write()?;
+        txn.open_table(COMMITMENTS)?;
+        txn.open_table(HEADER_TIMES)?;
+        txn.open_table(END_HEIGHTS)?;
+        txn.open_table(META)?;
+        txn.commit()?;
+
+        Ok(Self {
+            db,
+            from_block,
+            confirmations: 0,
+        })
+    }
+
+    /// Only index blocks with at least this many confirmations, to reduce the chance of rewinding
+    /// the index on reorgs.
+    pub fn with_confirmations(self, confirmations: u64) -> Self {
+        Self {
+            confirmations,
+            ..self
+        }
+    }
+
+    /// Indexes all events since the last synced block, up to the latest block with enough
+    /// confirmations. Returns the number of new commitments indexed.
+    #[instrument(target = "blobstream0::core", skip_all, err, level = Level::DEBUG)]
+    pub async fn sync<P, N>(&self, contract: &IBlobstreamInstance<P, N>) -> anyhow::Result<usize>
+    where
+        P: Provider<N>,
+        N: Network,
+    {
+        let provider = contract.provider();
+        let target = provider
+            .get_block_number()
+            .await?
+            .saturating_sub(self.confirmations);
+
+        let mut cursor = self.cursor()?;
+        if let Some((number, hash)) = cursor {
+            if block_hash(provider, number).await? != hash {
+                tracing::warn!(
+                    target: "blobstream0::core",
+                    "Reorg detected at indexed block {}, rewinding index",
+                    number
+                );
+                cursor = self.rewind(provider).await?;
+            }
+        }
+
+        let mut from = self.resume_from(cursor);
+        let mut indexed = 0;
+        while from <= target {
+            let to = target.min(from + LOG_QUERY_BLOCK_RANGE - 1);
+            let filter = Filter::new()
+                .address(*contract.address())
+                .event_signature(vec![
+                    DataCommitmentStored::SIGNATURE_HASH,
+                    ValidatorBitmapEquivocation::SIGNATURE_HASH,
+                    RangeHeaderTimes::SIGNATURE_HASH,
+                    HeadUpdate::SIGNATURE_HASH,
+                ])
+                .from_block(from)
+                .to_block(to);
+            let logs = provider.get_logs(&filter).await?;
+            let commitments = commitments_from_logs(&logs)?;
+            let to_hash = block_hash(provider, to).await?;
+
+            self.store(&commitments, (to, to_hash))?;
+
+            tracing::debug!(
+                target: "blobstream0::core",
+                "Indexed {} commitments in blocks {}-{}",
+                commitments.len(),
+                from,
+                to
+            );
+            indexed += commitments.len();
+            from = to + 1;
+        }
+
+        Ok(indexed)
+    }
+
+    /// Syncs the index with the contract every `interval`, until an error is encountered.
+    pub async fn run<P, N>(
+        &self,
+        contract: &IBlobstreamInstance<P, N>,
+        interval: Duration,
+    ) -> anyhow::Result<()>
+    where
+        P: Provider<N>,
+        N: Network,
+    {
+        loop {
+            let indexed = self.sync(contract).await?;
+            if indexed > 0 {
+                tracing::info!(target: "blobstream0::core", "Indexed {} new commitments", indexed);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// First block to index, after the cursor or from the configured block if nothing has been
+    /// indexed yet.
+    fn resume_from(&self, cursor: Option<(u64, B256)>) -> u64 {
+        cursor.map_or(self.from_block, |(number, _)| number + 1)
+    }
+
+    /// Stores the commitments indexed up to and including the cursor block.
+    fn store(&self, commitments: &[IndexedCommitment], cursor: (u64, B256)) -> anyhow::Result<()> {
+        let txn = self.db.begin_write()?;
+        {
+            let mut table = txn.open_table(COMMITMENTS)?;
+            let mut header_times = txn.open_table(HEADER_TIMES)?;
+            let mut end_heights = txn.open_table(END_HEIGHTS)?;
+            for commitment in commitments {
+                let nonce = commitment.nonce_key()?;
+                let record = CommitmentRecord::from(commitment).abi_encode();
+                table.insert(nonce, record.as_slice())?;
+                if let (Some(trusted_time), Some(time)) =
+                    (commitment.trusted_header_time, commitment.header_time)
+                {
+                    header_times.insert(nonce, (trusted_time, time))?;
+                }
+                end_heights.insert((commitment.commitment.end_block, nonce), ())?;
+            }
+            let (number, hash) = cursor;
+            txn.open_table(META)?.insert(CURSOR_KEY, (number, hash.0))?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Removes the commitments from blocks that are no longer in the canonical chain, and moves the
+    /// cursor back to the last commitment that is. Returns the new cursor.
+    async fn rewind<P, N>(&self, provider: &P) -> anyhow::Result<Option<(u64, B256)>>
+    where
+        P: Provider<N>,
+        N: Network,
+    {
+        self.rewind_with(
+            |number, hash| async move { Ok(block_hash(provider, number).await? == hash) },
+        )
+        .await
+    }
+
+    /// Rewinds the index with `is_canonical` returning whether the block with the number and hash
+    /// is still in the canonical chain.
+    async fn rewind_with<F, Fut>(&self, is_canonical: F) -> anyhow::Result<Option<(u64, B256)>>
+    where
+        F: Fn(u64, B256) -> Fut,
+        Fut: Future<Output = anyhow::Result<bool>>,
+    {
+        let records = {
+            let txn = self.db.begin_read()?;
+            let table = txn.open_table(COMMITMENTS)?;
+            table
+                .iter()?
+                .map(|entry| decode_record(entry?.1.value(), None))
+                .collect::<anyhow::Result<Vec<_>>>()?
+        };
+
+        // Remove the latest commitments until one is found in the canonical chain.
+        let mut removed = Vec::new();
+        let mut cursor = None;
+        for indexed in records.into_iter().rev() {
+            if is_canonical(indexed.eth_block_number, indexed.eth_block_hash).await? {
+                cursor = Some((indexed.eth_block_number, indexed.eth_block_hash));
+                break;
+            }
+            removed.push((indexed.nonce_key()?, indexed.commitment.end_block));
+        }
+
+        let txn = self.db.begin_write()?;
+        {
+            let mut table = txn.open_table(COMMITMENTS)?;
+            let mut header_times = txn.open_table(HEADER_TIMES)?;
+            let mut end_heights = txn.open_table(END_HEIGHTS)?;
+            for &(nonce, end_block) in &removed {
+                table.remove(nonce)?;
+                header_times.remove(nonce)?;
+                end_heights.remove((end_block, nonce))?;
+            }
+            let mut meta = txn.open_table(META)?;
+            match cursor {
+                Some((number, hash)) => meta.insert(CURSOR_KEY, (number, hash.0))?,
+                None => meta.remove(CURSOR_KEY)?,
+            };
+        }
+        txn.commit()?;
+
+        tracing::info!(
+            target: "blobstream0::core",
+            "Removed {} commitments no longer in the canonical chain",
+            removed.len()
+        );
+        Ok(cursor)
+    }
+
+    /// Number and hash of the last indexed block.
+    pub fn cursor(&self) -> anyhow::Result<Option<(u64, B256)>> {
+        let txn = self.db.begin_read()?;
+        let cursor = txn.open_table(META)?.get(CURSOR_KEY)?.map(|entry| {
+            let (number, hash) = entry.value();
+            (number, B256::from(hash))
+        });
+        Ok(cursor)
+    }
+
+    /// Proof nonce of the commitment that includes the data root of `height`.
+    pub fn nonce_for_height(&self, height: u64) -> anyhow::Result<Option<u64>> {
+        self.commitment_for_height(height)?
+            .map(|indexed| indexed.nonce_key())
+            .transpose()
+    }
+
+    /// Commitment that includes the data root of `height`. If there are multiple, as after the
+    /// trusted state is reset, this is the one ending closest to the height, and of those the
+    /// first stored.
+    pub fn commitment_for_height(&self, height: u64) -> anyhow::Result<Option<IndexedCommitment>> {
+        let txn = self.db.begin_read()?;
+        let end_heights = txn.open_table(END_HEIGHTS)?;
+        // Commitments ending later do not necessarily start later, so all of them are checked.
+        for entry in end_heights.range((height, 0)..)? {
+            let (key, _) = entry?;
+            let (_, nonce) = key.value();
+            let commitment = self
+                .commitment(nonce)?
+                .filter(|indexed| indexed.commitment.contains(height));
+            if commitment.is_some() {
+                return Ok(commitment);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Commitment stored at the proof nonce.
+    pub fn commitment(&self, nonce: u64) -> anyhow::Result<Option<IndexedCommitment>> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(COMMITMENTS)?;
+        let Some(record) = table.get(nonce)? else {
+            return Ok(None);
+        };
+        let times = txn
+            .open_table(HEADER_TIMES)?
+            .get(nonce)?
+            .map(|entry| entry.value());
+        Ok(Some(decode_record(record.value(), times)?))
+    }
+
+    /// All commitments that include the data root of any height from `start` to `end` inclusive,
+    /// in order of end height.
+    pub fn commitments_between(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> anyhow::Result<Vec<IndexedCommitment>> {
+        let txn = self.db.begin_read()?;
+        let end_heights = txn.open_table(END_HEIGHTS)?;
+        let table = txn.open_table(COMMITMENTS)?;
+        let header_times = txn.open_table(HEADER_TIMES)?;
+
+        let mut commitments = Vec::new();
+        for entry in end_heights.range((start, 0)..)? {
+            let (key, _) = entry?;
+            let (_, nonce) = key.value();
+            let record = table
+                .get(nonce)?
+                .with_context(|| format!("missing commitment for nonce {}", nonce))?;
+            let times = header_times.get(nonce)?.map(|entry| entry.value());
+            let indexed = decode_record(record.value(), times)?;
+            // Commitments ending later do not necessarily start later, so all of them are checked.
+            if indexed.commitment.start_block < end {
+                commitments.push(indexed);
+            }
+        }
+        Ok(commitments)
+    }
+}
+
+/// Decodes a commitment record, with the trusted and end header times if indexed.
+fn decode_record(bytes: &[u8], times: Option<(u64, u64)>) -> anyhow::Result<IndexedCommitment> {
+    let record = CommitmentRecord::abi_decode(bytes).context("invalid commitment record")?;
+    let mut indexed = IndexedCommitment::from(record);
+    if let Some((trusted_time, time)) = times {
+        indexed.trusted_header_time = Some(trusted_time);
+        indexed.header_time = Some(time);
+    }
+    Ok(indexed)
+}
+
+/// Combines the events emitted by each `updateRange` call into commitments. The logs must be in
+/// the order they were emitted.
+fn commitments_from_logs(logs: &[Log]) -> anyhow::Result<Vec<IndexedCommitment>> {
+    let mut commitments: Vec<IndexedCommitment> = Vec::new();
+    for log in logs {
+        let transaction_hash = log
+            .transaction_hash
+            .context("log missing transaction hash")?;
+        let topic0 = log.topic0().copied();
+        if topic0 == Some(DataCommitmentStored::SIGNATURE_HASH) {
+            let event = log.log_decode::<DataCommitmentStored>()?.inner.data;
+            commitments.push(IndexedCommitment {
+                commitment: DataCommitment::from(&event),
+                header_hash: B256::ZERO,
+                validator_bitmap: U256::ZERO,
+                trusted_header_time: None,
+                header_time: None,
+                eth_block_number: log.block_number.context("log missing block number")?,
+                eth_block_hash: log.block_hash.context("log missing block hash")?,
+                transaction_hash,
+            });
+            continue;
+        }
+
+        // The other events are emitted after the data commitment in the same transaction.
+        let Some(indexed) = commitments
+            .last_mut()
+            .filter(|indexed| indexed.transaction_hash == transaction_hash)
+        else {
+            continue;
+        };
+        if topic0 == Some(ValidatorBitmapEquivocation::SIGNATURE_HASH) {
+            let event = log.log_decode::<ValidatorBitmapEquivocation>()?.inner.data;
+            indexed.validator_bitmap = event.validatorBitmap;
+        } else if topic0 == Some(RangeHeaderTimes::SIGNATURE_HASH) {
+            let event = log.log_decode::<RangeHeaderTimes>()?.inner.data;
+            indexed.trusted_header_time = Some(event.trustedHeaderTime);
+            indexed.header_time = Some(event.newHeaderTime);
+        } else if topic0 == Some(HeadUpdate::SIGNATURE_HASH) {
+            let event = log.log_decode::<HeadUpdate>()?.inner.data;
+            indexed.header_hash = event.headerHash;
+        }
+    }
+    Ok(commitments)
+}
+
+async fn block_hash<P, N>(provider: &P, number: u64) -> anyhow::Result<B256>
+where
+    P: Provider<N>,
+    N: Network,
+{
+    let block = provider
+        .get_block_by_number(number.into())
+        .await?
+        .with_context(|| format!("block {} not found", number))?;
+    Ok(block.header().hash())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Database file removed when dropped.
+    struct TempDb(PathBuf);
+
+    impl TempDb {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "blobstream0-indexer-{}-{}.redb",
+                name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_file(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDb {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn commitment(
+        nonce: u64,
+        start_block: u64,
+        end_block: u64,
+        eth_block: u64,
+    ) -> IndexedCommitment {
+        IndexedCommitment {
+            commitment: DataCommitment {
+                proof_nonce: U256::from(nonce),
+                start_block,
+                end_block,
+                data_commitment: B256::with_last_byte(nonce as u8),
+            },
+            header_hash: B256::ZERO,
+            validator_bitmap: U256::ZERO,
+            trusted_header_time: Some(start_block * 10),
+            header_time: Some(end_block * 10),
+            eth_block_number: eth_block,
+            eth_block_hash: B256::with_last_byte(eth_block as u8),
+            transaction_hash: B256::ZERO,
+        }
+    }
+
+    fn nonces(commitments: &[IndexedCommitment]) -> Vec<U256> {
+        commitments
+            .iter()
+            .map(|indexed| indexed.commitment.proof_nonce)
+            .collect()
+    }
+
+    #[test]
+    fn resumes_after_restart() -> anyhow::Result<()> {
+        let db = TempDb::new("resume");
+        {
+            let indexer = Indexer::open(&db.0, 100)?;
+            assert_eq!(indexer.resume_from(indexer.cursor()?), 100);
+            indexer.store(&[commitment(1, 10, 20, 105)], (110, B256::repeat_byte(1)))?;
+        }
+
+        let indexer = Indexer::open(&db.0, 100)?;
+        let cursor = indexer.cursor()?;
+        assert_eq!(cursor, Some((110, B256::repeat_byte(1))));
+        assert_eq!(indexer.resume_from(cursor), 111);
+        assert_eq!(indexer.commitment(1)?, Some(commitment(1, 10, 20, 105)));
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_commitments_with_same_end_height() -> anyhow::Result<()> {
+        let db = TempDb::new("same-end");
+        let indexer = Indexer::open(&db.0, 0)?;
+        // After the trusted state is reset to height 15, a range ending at 20 is proven again.
+        indexer.store(
+            &[
+                commitment(1, 10, 20, 1),
+                commitment(2, 20, 30, 2),
+                commitment(3, 15, 20, 3),
+            ],
+            (3, B256::repeat_byte(3)),
+        )?;
+
+        assert_eq!(
+            nonces(&indexer.commitments_between(11, 30)?),
+            [1, 3, 2].map(U256::from)
+        );
+        assert_eq!(indexer.nonce_for_height(12)?, Some(1));
+        assert_eq!(indexer.nonce_for_height(18)?, Some(1));
+        assert_eq!(indexer.nonce_for_height(25)?, Some(2));
+        assert_eq!(indexer.nonce_for_height(31)?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rewinds_to_canonical_commitment() -> anyhow::Result<()> {
+        let db = TempDb::new("rewind");
+        let indexer = Indexer::open(&db.0, 0)?;
+        indexer.store(
+            &[
+                commitment(1, 10, 20, 1),
+                commitment(2, 20, 30, 2),
+                commitment(3, 20, 30, 3),
+            ],
+            (3, B256::repeat_byte(3)),
+        )?;
+
+        // Blocks after 1 were reorged out.
+        let cursor = indexer
+            .rewind_with(|number, _| async move { Ok(number <= 1) })
+            .await?;
+        assert_eq!(cursor, Some((1, B256::with_last_byte(1))));
+        assert_eq!(indexer.cursor()?, cursor);
+        assert_eq!(indexer.resume_from(cursor), 2);
+        assert_eq!(indexer.commitment(2)?, None);
+        assert_eq!(indexer.commitment(3)?, None);
+        assert_eq!(
+            nonces(&indexer.commitments_between(0, 100)?),
+            [U256::from(1)]
+        );
+        assert_eq!(indexer.nonce_for_height(25)?, None);
+
+        // Nothing is left when no commitment is canonical.
+        let cursor = indexer.rewind_with(|_, _| async { Ok(false) }).await?;
+        assert_eq!(cursor, None);
+        assert_eq!(indexer.cursor()?, None);
+        assert_eq!(indexer.resume_from(cursor), 0);
+        assert!(indexer.commitments_between(0, 100)?.is_empty());
+        Ok(())
+    }
+}
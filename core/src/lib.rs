@@ -16,20 +16,47 @@ use alloy::sol_types::SolValue;
 use alloy::{network::Network, providers::Provider};
 use anyhow::Context;
 use blobstream0_primitives::{
+    chain_id_hash, header_time,
     proto::{TrustedLightBlock, UntrustedLightBlock},
     IBlobstream::IBlobstreamInstance,
-    LightBlockProveData, RangeCommitment,
+    LightBlockProveData, ProofConfig, RangeCommitment,
 };
 use risc0_ethereum_contracts::groth16;
-use risc0_zkvm::{default_prover, is_dev_mode, sha::Digestible, ExecutorEnv, ProverOpts, Receipt};
-use std::{ops::Range, sync::Arc, time::Duration};
+use risc0_zkvm::{
+    compute_image_id, default_executor, default_prover, is_dev_mode, sha::Digestible, ExecutorEnv,
+    InnerReceipt, ProverOpts,
+};
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use tendermint::{block::Height, validator::Set};
 use tendermint_light_client_verifier::types::Header;
-use tendermint_proto::{types::Header as ProtoHeader, Protobuf};
 use tendermint_rpc::{Client, HttpClient, Paging};
 use tokio::{sync::Semaphore, task::JoinHandle};
 use tracing::{instrument, Level};
 
+mod client;
+pub use client::tendermint_client;
+
+mod error;
+pub use error::{ContractError, IRiscZeroVerifierErrors};
+
+pub use risc0_zkvm::{sha::Digest, Receipt};
+
+mod inclusion;
+pub use inclusion::{
+    data_root_inclusion_proof, find_data_commitment, header_hash_inclusion_proof, DataCommitment,
+    DataRootInclusionProof, HeaderHashInclusionProof,
+};
+
+mod indexer;
+pub use indexer::{IndexedCommitment, Indexer};
+
 mod range_iterator;
 use range_iterator::LightBlockRangeIterator;
 
@@ -45,6 +72,28 @@ use light_client_guest::LIGHT_CLIENT_GUEST_ELF;
 /// Currently set to the max allowed by tendermint RPC
 const HEADER_REQ_COUNT: u64 = 20;
 
+/// Prefix of the lines the guest prints to stderr with the cycles of each phase, when built with the
+/// `cycle-report` feature.
+const CYCLE_REPORT_PREFIX: &str = "blobstream0-cycles";
+
+/// Image ID of the light client guest program, which range proofs are generated with.
+pub fn light_client_image_id() -> anyhow::Result<Digest> {
+    Ok(compute_image_id(LIGHT_CLIENT_GUEST_ELF)?)
+}
+
+/// Verifies a receipt against the image ID and decodes the [RangeCommitment] from its journal.
+pub fn verify_range_receipt(
+    receipt: &Receipt,
+    image_id: impl Into<Digest>,
+) -> anyhow::Result<RangeCommitment> {
+    receipt
+        .verify(image_id)
+        .context("receipt verification failed")?;
+    let commitment = RangeCommitment::abi_decode_validate(&receipt.journal.bytes)
+        .context("receipt journal is not a range commitment")?;
+    Ok(commitment)
+}
+
 async fn fetch_validators(client: &HttpClient, block_height: Height) -> anyhow::Result<Set> {
     // Note: This currently needs to use Paging::All or the hash mismatches.
     let validator_response = client.validators(block_height, Paging::All).await?;
@@ -138,36 +187,37 @@ pub async fn fetch_headers(
     Ok(all_blocks)
 }
 
-/// Prove a single block with the trusted light client block and the height to fetch and prove.
-#[instrument(
-    target = "blobstream0::core",
-    skip(input),
-    fields(light_range = ?input.untrusted_height()..input.trusted_height()),
-    err, level = Level::DEBUG)]
-pub async fn prove_block(input: LightBlockProveData) -> anyhow::Result<Receipt> {
+/// Encodes the inputs for the light client guest, prefixed with the length of the encoded data.
+/// The proof config is encoded first, as it selects the protobuf version of the rest.
+fn encode_guest_input(input: LightBlockProveData) -> anyhow::Result<Vec<u8>> {
     let mut buffer = Vec::<u8>::new();
     assert_eq!(
         input.untrusted_height() - input.trusted_height() - 1,
         input.interval_headers.len() as u64
     );
-    let expected_next_hash = input.untrusted_block.signed_header.header().hash();
-    let expected_next_height = input.untrusted_height();
-    let expected_trusted_hash = input.trusted_block.signed_header.header().hash();
 
-    TrustedLightBlock {
-        signed_header: input.trusted_block.signed_header,
-        next_validators: input.trusted_block.next_validators,
-    }
-    .encode_length_delimited(&mut buffer)?;
+    let config = input.config;
+    let proto_version = config.proto_version;
+    buffer.extend_from_slice(&config.encode());
 
-    UntrustedLightBlock {
-        signed_header: input.untrusted_block.signed_header,
-        validators: input.untrusted_block.validators,
-    }
-    .encode_length_delimited(&mut buffer)?;
+    proto_version.encode_light_block(
+        TrustedLightBlock {
+            signed_header: input.trusted_block.signed_header,
+            next_validators: input.trusted_block.next_validators,
+        },
+        &mut buffer,
+    )?;
+
+    proto_version.encode_light_block(
+        UntrustedLightBlock {
+            signed_header: input.untrusted_block.signed_header,
+            validators: input.untrusted_block.validators,
+        },
+        &mut buffer,
+    )?;
 
     for header in input.interval_headers {
-        Protobuf::<ProtoHeader>::encode_length_delimited(header, &mut buffer)?;
+        proto_version.encode_header(header, &mut buffer)?;
     }
 
     let buffer_len: u32 = buffer
@@ -175,13 +225,72 @@ pub async fn prove_block(input: LightBlockProveData) -> anyhow::Result<Receipt>
         .try_into()
         .expect("buffer cannot exceed 32 bit range");
 
+    Ok([&buffer_len.to_le_bytes()[..], &buffer].concat())
+}
+
+/// Expected values of the [RangeCommitment] for the inputs to the guest.
+struct ExpectedCommitment {
+    trusted_hash: tendermint::Hash,
+    trusted_height: u64,
+    trusted_time: u64,
+    next_hash: tendermint::Hash,
+    next_height: u64,
+    next_time: u64,
+    chain_id_hash: [u8; 32],
+    leaf_content: u8,
+}
+
+impl ExpectedCommitment {
+    fn new(input: &LightBlockProveData) -> Self {
+        let trusted_header = input.trusted_block.signed_header.header();
+        let next_header = input.untrusted_block.signed_header.header();
+        Self {
+            trusted_hash: trusted_header.hash(),
+            trusted_height: input.trusted_height(),
+            trusted_time: header_time(trusted_header),
+            next_hash: next_header.hash(),
+            next_height: input.untrusted_height(),
+            next_time: header_time(next_header),
+            chain_id_hash: chain_id_hash(&trusted_header.chain_id),
+            leaf_content: input.config.leaf_content.into(),
+        }
+    }
+
+    /// Assert that what is proven is expected based on the inputs.
+    fn check(&self, commitment: &RangeCommitment) {
+        assert_eq!(self.next_hash.as_bytes(), commitment.newHeaderHash);
+        assert_eq!(self.next_height, commitment.newHeight);
+        assert_eq!(
+            self.trusted_hash.as_bytes(),
+            commitment.trustedHeaderHash.as_slice()
+        );
+        assert_eq!(commitment.chainIdHash, self.chain_id_hash);
+        assert_eq!(self.trusted_height, commitment.trustedHeight);
+        assert_eq!(self.trusted_time, commitment.trustedHeaderTime);
+        assert_eq!(self.next_time, commitment.newHeaderTime);
+        assert_eq!(self.leaf_content, commitment.leafContent);
+    }
+}
+
+/// Prove a single block with the trusted light client block and the height to fetch and prove.
+#[instrument(
+    target = "blobstream0::core",
+    skip(input),
+    fields(light_range = ?input.untrusted_height()..input.trusted_height()),
+    err, level = Level::DEBUG)]
+pub async fn prove_block(input: LightBlockProveData) -> anyhow::Result<Receipt> {
+    anyhow::ensure!(
+        !cfg!(feature = "cycle-report"),
+        "proving is disabled with the cycle-report feature, as the instrumented guest does not \
+         match the deployed image ID"
+    );
+    let expected = ExpectedCommitment::new(&input);
+    let input = encode_guest_input(input)?;
+
     tracing::debug!(target: "blobstream0::core", "Proving light client");
     // Note: must be in blocking context to not have issues with Bonsai blocking client when selected
     let prove_info = tokio::task::spawn_blocking(move || {
-        let env = ExecutorEnv::builder()
-            .write_slice(&[buffer_len])
-            .write_slice(&buffer)
-            .build()?;
+        let env = ExecutorEnv::builder().write_slice(&input).build()?;
 
         let prover = default_prover();
         prover.prove_with_opts(env, LIGHT_CLIENT_GUEST_ELF, &ProverOpts::groth16())
@@ -189,23 +298,119 @@ pub async fn prove_block(input: LightBlockProveData) -> anyhow::Result<Receipt>
     .await??;
     let receipt = prove_info.receipt;
     let commitment = RangeCommitment::abi_decode_validate(&receipt.journal.bytes)?;
-    // Assert that what is proven is expected based on the inputs.
-    assert_eq!(expected_next_hash.as_bytes(), commitment.newHeaderHash);
-    assert_eq!(expected_next_height, commitment.newHeight);
-    assert_eq!(
-        expected_trusted_hash.as_bytes(),
-        commitment.trustedHeaderHash.as_slice()
-    );
+    expected.check(&commitment);
 
     Ok(receipt)
 }
 
-/// Fetches and proves a range of light client blocks.
-#[instrument(target = "blobstream0::core", skip(client), err, level = Level::INFO)]
-pub async fn prove_block_range(
+/// Report of executing the light client guest without proving.
+#[derive(Debug, Clone)]
+pub struct ExecutionReport {
+    /// Total cycles of all segments, including padding to the segment size, which is what the
+    /// proving cost is based on.
+    pub total_cycles: u64,
+    /// Cycles used by the guest, without any continuation overhead or padding.
+    pub user_cycles: u64,
+    /// Number of segments the execution was split into.
+    pub segments: usize,
+    /// Size of the encoded guest input in bytes.
+    pub input_bytes: usize,
+    /// Commitment written to the journal.
+    pub commitment: RangeCommitment,
+    /// User cycles of each phase of the guest, only reported with the `cycle-report` feature.
+    pub phase_cycles: Option<PhaseCycles>,
+}
+
+/// User cycles of each phase of the light client guest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PhaseCycles {
+    /// Decoding the light blocks and headers.
+    pub decode: u64,
+    /// Generating the validator bitmap.
+    pub bitmap: u64,
+    /// Checking hash links and building the merkle root of the data roots.
+    pub merkle_root: u64,
+    /// Verifying the light client transition with the validator signatures.
+    pub light_client_verify: u64,
+}
+
+impl PhaseCycles {
+    /// Parses the phase cycles printed by the guest to stderr. Returns [None] if any phase is
+    /// missing.
+    fn from_guest_output(output: &[u8]) -> Option<Self> {
+        let mut phases = [None; 4];
+        for line in String::from_utf8_lossy(output).lines() {
+            let mut parts = line.split_whitespace();
+            if parts.next() != Some(CYCLE_REPORT_PREFIX) {
+                continue;
+            }
+            let index = match parts.next()? {
+                "decode" => 0,
+                "bitmap" => 1,
+                "merkle_root" => 2,
+                "light_client_verify" => 3,
+                _ => continue,
+            };
+            phases[index] = Some(parts.next()?.parse().ok()?);
+        }
+        let [decode, bitmap, merkle_root, light_client_verify] = phases;
+        Some(Self {
+            decode: decode?,
+            bitmap: bitmap?,
+            merkle_root: merkle_root?,
+            light_client_verify: light_client_verify?,
+        })
+    }
+}
+
+/// Executes the light client guest on the inputs without generating a proof, which is much faster
+/// than proving and reports the cycles it would take to prove.
+#[instrument(
+    target = "blobstream0::core",
+    skip(input),
+    fields(light_range = ?input.untrusted_height()..input.trusted_height()),
+    err, level = Level::DEBUG)]
+pub async fn execute_block(input: LightBlockProveData) -> anyhow::Result<ExecutionReport> {
+    let expected = ExpectedCommitment::new(&input);
+    let input = encode_guest_input(input)?;
+    let input_bytes = input.len();
+
+    tracing::debug!(target: "blobstream0::core", "Executing light client");
+    let (session, guest_output) = tokio::task::spawn_blocking(move || {
+        let mut guest_output = Vec::new();
+        let env = ExecutorEnv::builder()
+            .write_slice(&input)
+            .stderr(&mut guest_output)
+            .build()?;
+
+        let session = default_executor().execute(env, LIGHT_CLIENT_GUEST_ELF)?;
+        anyhow::Ok((session, guest_output))
+    })
+    .await??;
+    let commitment = RangeCommitment::abi_decode_validate(&session.journal.bytes)?;
+    expected.check(&commitment);
+
+    Ok(ExecutionReport {
+        total_cycles: session
+            .segments
+            .iter()
+            .map(|segment| 1u64 << segment.po2)
+            .sum(),
+        user_cycles: session.cycles(),
+        segments: session.segments.len(),
+        input_bytes,
+        commitment,
+        phase_cycles: PhaseCycles::from_guest_output(&guest_output),
+    })
+}
+
+/// Fetches the inputs to prove a range of light client blocks. The inputs may end before the end
+/// of the range, if the validator set changes too much to verify the last block directly.
+async fn fetch_block_range_input(
     client: Arc<HttpClient>,
     range: Range<u64>,
-) -> anyhow::Result<Receipt> {
+    config: ProofConfig,
+) -> anyhow::Result<LightBlockProveData> {
     // Include fetching the trusted light client block from before the range.
     let (trusted_block, blocks) = tokio::try_join!(
         fetch_trusted_light_block(&client, Height::try_from(range.start - 1)?),
@@ -216,17 +421,193 @@ pub async fn prove_block_range(
         client: &client,
         trusted_block,
         blocks: &blocks,
+        config,
     };
 
-    let inputs = range_iterator
+    range_iterator
         .next_range()
         .await?
-        .context("unable to prove any blocks in the range")?;
+        .context("unable to prove any blocks in the range")
+}
+
+/// Fetches and proves a range of light client blocks.
+#[instrument(target = "blobstream0::core", skip(client), err, level = Level::INFO)]
+pub async fn prove_block_range(
+    client: Arc<HttpClient>,
+    range: Range<u64>,
+    config: ProofConfig,
+) -> anyhow::Result<Receipt> {
+    let inputs = fetch_block_range_input(client, range, config).await?;
     let receipt = prove_block(inputs).await?;
 
     Ok(receipt)
 }
 
+/// Fetches a range of light client blocks and executes the guest on them without proving.
+#[instrument(target = "blobstream0::core", skip(client), err, level = Level::INFO)]
+pub async fn execute_block_range(
+    client: Arc<HttpClient>,
+    range: Range<u64>,
+    config: ProofConfig,
+) -> anyhow::Result<ExecutionReport> {
+    let inputs = fetch_block_range_input(client, range, config).await?;
+    execute_block(inputs).await
+}
+
+/// Length of the next batch of the `remaining` blocks, `batch_size` blocks unless the blocks after
+/// would be a tail of `min_batch_size` or fewer blocks, which the contract would reject. The tail
+/// is then included in this batch, so the last batch can be up to `batch_size + min_batch_size`
+/// blocks.
+fn next_batch_len(remaining: usize, batch_size: usize, min_batch_size: usize) -> usize {
+    if remaining <= batch_size + min_batch_size {
+        remaining
+    } else {
+        batch_size
+    }
+}
+
+/// Fetches a range of light client blocks and plans the inputs for consecutive proofs over the
+/// whole range, each covering at most `batch_size` blocks. A tail of `min_batch_size` blocks or
+/// fewer is merged into the previous batch, as the contract only accepts updates of more than
+/// `min_batch_size` blocks.
+///
+/// Batches are split where the validator set changes too much to verify in a single step, and
+/// the blocks after a split are proven with the following batch. Planning fails before any
+/// proving if a split still leaves a proof of `min_batch_size` blocks or fewer.
+///
+/// Each input is trusted from the last block of the previous one, so all proofs can be generated
+/// independently of each other.
+#[instrument(target = "blobstream0::core", skip(client), err, level = Level::INFO)]
+pub async fn plan_block_ranges(
+    client: Arc<HttpClient>,
+    range: Range<u64>,
+    batch_size: u64,
+    min_batch_size: u64,
+    config: ProofConfig,
+) -> anyhow::Result<Vec<LightBlockProveData>> {
+    anyhow::ensure!(batch_size > 0, "batch size must be greater than zero");
+    let (mut trusted_block, blocks) = tokio::try_join!(
+        fetch_trusted_light_block(&client, Height::try_from(range.start - 1)?),
+        fetch_headers(client.clone(), range.start..range.end)
+    )?;
+
+    let mut inputs = Vec::new();
+    let mut remaining = blocks.as_slice();
+    while !remaining.is_empty() {
+        let len = next_batch_len(
+            remaining.len(),
+            batch_size as usize,
+            min_batch_size as usize,
+        );
+        let trusted_height = trusted_block.signed_header.header.height;
+        let mut range_iterator = LightBlockRangeIterator {
+            client: &client,
+            trusted_block,
+            blocks: &remaining[..len],
+            config,
+        };
+        let input = range_iterator.next_range().await?.with_context(|| {
+            format!("unable to prove any blocks after height {}", trusted_height)
+        })?;
+
+        // Only a range too short for the contract as a whole is allowed to stay short.
+        let proven = input.interval_headers.len() + 1;
+        anyhow::ensure!(
+            proven > min_batch_size as usize || proven == blocks.len(),
+            "validator set changes too much after height {} to prove more than the minimum batch \
+             size of {} blocks, which the contract would reject",
+            trusted_height,
+            min_batch_size
+        );
+        remaining = &remaining[proven..];
+        trusted_block = range_iterator.trusted_block;
+        inputs.push(input);
+    }
+
+    Ok(inputs)
+}
+
+/// Proves all blocks in the range, split into batches of at most `batch_size` blocks, and posts the
+/// proofs to the contract. Up to `parallelism` proofs are generated concurrently, and the proofs
+/// are posted in order as they complete.
+///
+/// If proving or posting a batch fails, no further proofs are started, but proofs that are
+/// already running cannot be interrupted and finish in the background after this returns.
+///
+/// Returns the number of batches posted.
+#[instrument(target = "blobstream0::core", skip(client, contract), err, level = Level::INFO)]
+pub async fn prove_and_post_block_ranges<P, N>(
+    client: Arc<HttpClient>,
+    contract: &IBlobstreamInstance<P, N>,
+    range: Range<u64>,
+    batch_size: u64,
+    parallelism: usize,
+    config: ProofConfig,
+) -> anyhow::Result<usize>
+where
+    P: Provider<N>,
+    N: Network,
+{
+    let min_batch_size = contract
+        .minBatchSize()
+        .call()
+        .await
+        .map_err(ContractError::from)?;
+    let inputs = plan_block_ranges(client, range, batch_size, min_batch_size, config).await?;
+    tracing::info!(
+        target: "blobstream0::core",
+        "Proving {} batches with parallelism {}",
+        inputs.len(),
+        parallelism
+    );
+
+    let semaphore = Arc::new(Semaphore::new(parallelism.max(1)));
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let mut jhs: Vec<JoinHandle<anyhow::Result<Receipt>>> = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let semaphore = semaphore.clone();
+        let cancelled = cancelled.clone();
+        jhs.push(tokio::spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            // Checked once a proof can start, as proving cannot be interrupted after.
+            anyhow::ensure!(!cancelled.load(Ordering::Acquire), "proving was cancelled");
+            prove_block(input).await
+        }));
+    }
+
+    let num_batches = jhs.len();
+    for (i, jh) in jhs.into_iter().enumerate() {
+        let result = async {
+            let receipt = jh.await??;
+            post_batch(contract, &receipt).await
+        }
+        .await;
+        if let Err(e) = result {
+            // Later proofs depend on this batch being posted, so do not start any more.
+            cancelled.store(true, Ordering::Release);
+            return Err(e.context(format!("failed on batch {} of {}", i + 1, num_batches)));
+        }
+        tracing::info!(
+            target: "blobstream0::core",
+            "Posted batch {} of {}",
+            i + 1,
+            num_batches
+        );
+    }
+
+    Ok(num_batches)
+}
+
+/// Encodes the seal of a receipt as passed to `updateRange`. Groth16 receipts are encoded for the
+/// Groth16 verifier, and fake receipts from dev mode for the mock verifier with a zero selector.
+pub fn encode_seal(receipt: &Receipt) -> anyhow::Result<Vec<u8>> {
+    match &receipt.inner {
+        InnerReceipt::Groth16(inner) => groth16::encode(inner.seal.clone()),
+        InnerReceipt::Fake(_) => Ok([&[0u8; 4], receipt.claim()?.digest().as_bytes()].concat()),
+        _ => anyhow::bail!("receipt must be a Groth16 receipt, or a fake receipt from dev mode"),
+    }
+}
+
 /// Post batch proof to Eth based chain.
 #[instrument(target = "blobstream0::core", skip(contract, receipt), err, level = Level::DEBUG)]
 pub async fn post_batch<P, N>(
@@ -238,18 +619,56 @@ where
     N: Network,
 {
     tracing::debug!(target: "blobstream0::core", "Posting batch (dev mode={})", is_dev_mode());
-    let seal = match is_dev_mode() {
-        true => [&[0u8; 4], receipt.claim()?.digest().as_bytes()].concat(),
-        false => groth16::encode(receipt.inner.groth16()?.seal.clone())?,
-    };
+    let seal = encode_seal(receipt)?;
 
     let update_tx = contract.updateRange(receipt.journal.bytes.clone().into(), seal.into());
     update_tx
         .send()
-        .await?
+        .await
+        .map_err(ContractError::from)?
         .with_timeout(Some(Duration::from_secs(300)))
         .watch()
-        .await?;
+        .await
+        .map_err(ContractError::from)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::next_batch_len;
+
+    /// Lengths of the batches of `len` blocks, when no batch is split by validator set changes.
+    fn batch_lengths(len: usize, batch_size: usize, min_batch_size: usize) -> Vec<usize> {
+        let mut lengths = Vec::new();
+        let mut remaining = len;
+        while remaining > 0 {
+            let len = next_batch_len(remaining, batch_size, min_batch_size);
+            lengths.push(len);
+            remaining -= len;
+        }
+        lengths
+    }
+
+    #[test]
+    fn batch_lengths_divisible() {
+        assert_eq!(batch_lengths(24, 8, 3), [8, 8, 8]);
+        assert_eq!(batch_lengths(0, 8, 3), [] as [usize; 0]);
+    }
+
+    #[test]
+    fn batch_lengths_long_tail() {
+        // Tail longer than the minimum batch size is kept as its own batch.
+        assert_eq!(batch_lengths(28, 8, 3), [8, 8, 8, 4]);
+    }
+
+    #[test]
+    fn batch_lengths_short_tail() {
+        // Tail the contract would reject is merged into the previous batch.
+        assert_eq!(batch_lengths(27, 8, 3), [8, 8, 11]);
+        assert_eq!(batch_lengths(25, 8, 3), [8, 8, 9]);
+        assert_eq!(batch_lengths(11, 8, 3), [11]);
+        // Nothing to merge into when the whole range is shorter than a batch.
+        assert_eq!(batch_lengths(2, 8, 3), [2]);
+    }
+}
@@ -17,7 +17,7 @@
 use blobstream0_primitives::{
     light_client_verify,
     proto::{TrustedLightBlock, UntrustedLightBlock},
-    LightBlockProveData,
+    LightBlockProveData, ProofConfig,
 };
 use tendermint_light_client_verifier::{types::Header, Verdict};
 use tendermint_rpc::HttpClient;
@@ -30,6 +30,7 @@ pub struct LightBlockRangeIterator<'a> {
     pub client: &'a HttpClient,
     pub trusted_block: TrustedLightBlock,
     pub blocks: &'a [Header],
+    pub config: ProofConfig,
 }
 
 impl LightBlockRangeIterator<'_> {
@@ -95,6 +96,7 @@ impl LightBlockRangeIterator<'_> {
             trusted_block: old_trusted_block,
             interval_headers: header_blocks.to_vec(),
             untrusted_block,
+            config: self.config,
         }))
     }
 }
@@ -40,6 +40,7 @@ fn main() {
     // guest. Check the RISC0_USE_DOCKER variable and use Docker to build the guest if set.
     println!("cargo:rerun-if-env-changed=RISC0_USE_DOCKER");
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=CARGO_FEATURE_CYCLE_REPORT");
     let manifest_dir = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap());
     let mut builder = GuestOptionsBuilder::default();
     if env::var("RISC0_USE_DOCKER").is_ok() {
@@ -49,11 +50,20 @@ fn main() {
             .unwrap();
         builder.use_docker(docker_options);
     }
+    let cycle_report = env::var("CARGO_FEATURE_CYCLE_REPORT").is_ok();
+    if cycle_report {
+        builder.features(vec!["cycle-report".to_string()]);
+    }
     let guest_options = builder.build().unwrap();
 
     // Generate Rust source files for the methods crate.
     let guests = embed_methods_with_options(HashMap::from([("light-client-guest", guest_options)]));
 
+    // The instrumented guest has a different image ID, which must not be written to the contracts.
+    if cycle_report {
+        return;
+    }
+
     // Generate Solidity source files for use with Forge.
     let solidity_opts = risc0_build_ethereum::Options::default()
         .with_image_id_sol_path(SOLIDITY_IMAGE_ID_PATH)
@@ -14,13 +14,32 @@
 
 use alloy_sol_types::SolValue;
 use blobstream0_primitives::proto::{TrustedLightBlock, UntrustedLightBlock};
-use blobstream0_primitives::{build_merkle_root, expect_block_hash, light_client_verify};
-use blobstream0_primitives::{generate_bitmap, RangeCommitment};
+use blobstream0_primitives::{
+    build_merkle_root, chain_id_hash, expect_block_hash, header_time, light_client_verify,
+};
+use blobstream0_primitives::{generate_bitmap, ProofConfig, RangeCommitment};
 use risc0_zkvm::guest::env;
-use tendermint_light_client_verifier::{types::Header, Verdict};
-use tendermint_proto::Protobuf;
+use tendermint_light_client_verifier::Verdict;
+
+/// Prints the cycles used since the previous phase to stderr. Only compiled in with the
+/// `cycle-report` feature, which is used for benchmarking, as it changes the image ID.
+#[cfg(feature = "cycle-report")]
+fn report_cycles(phase: &str, last_cycle_count: &mut u64) {
+    let cycle_count = env::cycle_count();
+    eprintln!(
+        "blobstream0-cycles {} {}",
+        phase,
+        cycle_count - *last_cycle_count
+    );
+    *last_cycle_count = cycle_count;
+}
+
+#[cfg(not(feature = "cycle-report"))]
+fn report_cycles(_phase: &str, _last_cycle_count: &mut u64) {}
 
 fn main() {
+    let mut last_cycle_count = 0;
+
     // Deserialize inputs from host.
     let mut len: u32 = 0;
     env::read_slice(core::slice::from_mut(&mut len));
@@ -29,29 +48,37 @@ fn main() {
 
     let mut cursor = buf.as_slice();
 
-    let trusted_block = TrustedLightBlock::decode_length_delimited(&mut cursor).unwrap();
-    let untrusted_block = UntrustedLightBlock::decode_length_delimited(&mut cursor).unwrap();
+    // The config selects the protobuf version of the inputs that follow it.
+    let config = ProofConfig::decode(&mut cursor).unwrap();
+    let proto_version = config.proto_version;
+
+    let trusted_block: TrustedLightBlock = proto_version.decode_light_block(&mut cursor).unwrap();
+    let untrusted_block: UntrustedLightBlock =
+        proto_version.decode_light_block(&mut cursor).unwrap();
 
     let num_headers = untrusted_block.signed_header.header.height.value()
         - trusted_block.signed_header.header.height.value()
         - 1;
     let mut interval_headers = Vec::with_capacity(num_headers.try_into().unwrap());
     for _ in 0..num_headers {
-        let header: Header =
-            Protobuf::<tendermint_proto::v0_37::types::Header>::decode_length_delimited(
-                &mut cursor,
-            )
-            .unwrap();
-        interval_headers.push(header);
+        interval_headers.push(proto_version.decode_header(&mut cursor).unwrap());
     }
     // Assert all bytes have been read, as a sanity check.
     assert!(cursor.is_empty());
+    report_cycles("decode", &mut last_cycle_count);
 
     // Generate validator bitmap of intersection of trusted and untrusted block signatures.
     let validator_bitmap = generate_bitmap(&trusted_block, &untrusted_block);
+    report_cycles("bitmap", &mut last_cycle_count);
 
-    // Build merkle root, while also verifying hash links between all blocks.
-    let merkle_root = build_merkle_root(&trusted_block, &interval_headers, &untrusted_block);
+    // Build merkle roots, while also verifying hash links between all blocks.
+    let merkle_roots = build_merkle_root(
+        &trusted_block,
+        &interval_headers,
+        &untrusted_block,
+        config.leaf_content,
+    );
+    report_cycles("merkle_root", &mut last_cycle_count);
 
     // Verify the light client transition to untrusted block
     let verdict = light_client_verify(&trusted_block, &untrusted_block);
@@ -60,14 +87,26 @@ fn main() {
         "validation failed, {:?}",
         verdict
     );
+    report_cycles("light_client_verify", &mut last_cycle_count);
 
     // Commit ABI encoded data to journal to use in contract.
+    let trusted_header = trusted_block.signed_header.header();
+    let untrusted_header = untrusted_block.signed_header.header();
     let commit = RangeCommitment {
-        trustedHeaderHash: expect_block_hash(trusted_block.signed_header.header()).into(),
-        newHeight: untrusted_block.signed_header.header.height.value(),
-        newHeaderHash: expect_block_hash(untrusted_block.signed_header.header()).into(),
-        merkleRoot: merkle_root.into(),
+        trustedHeaderHash: expect_block_hash(trusted_header).into(),
+        newHeight: untrusted_header.height.value(),
+        newHeaderHash: expect_block_hash(untrusted_header).into(),
+        merkleRoot: merkle_roots.data_root.into(),
         validatorBitmap: validator_bitmap.into(),
+        // The light client verification checks both headers are of the same chain.
+        chainIdHash: chain_id_hash(&trusted_header.chain_id).into(),
+        // The contract checks the trusted header is within the trusting period at the time the
+        // proof is posted, as the guest can only check it relative to the new header.
+        trustedHeight: trusted_header.height.value(),
+        trustedHeaderTime: header_time(trusted_header),
+        newHeaderTime: header_time(untrusted_header),
+        headerHashRoot: merkle_roots.header_hash_root.into(),
+        leafContent: config.leaf_content.into(),
     };
     env::commit_slice(commit.abi_encode().as_slice());
 }
@@ -0,0 +1,124 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Configuration of what the light client guest proves, to support CometBFT chains other than
+//! Celestia.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::proto::ProtoVersion;
+
+/// Error for an unknown or missing configuration value.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("unknown leaf content `{0}`, expected one of data-hash, app-hash or header-hash")]
+    UnknownLeafContent(String),
+    #[error("unknown proto version `{0}`, expected one of 0.34, 0.37 or 0.38")]
+    UnknownProtoVersion(String),
+    #[error("proof config is missing from the guest input")]
+    Missing,
+}
+
+/// Header field committed to as the root of each leaf in the merkle tree of a range.
+///
+/// The value is committed by the guest and must match the one the contract is initialized with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LeafContent {
+    /// The data hash, which for Celestia is the data root of the block. Headers without a data
+    /// hash commit to zero.
+    #[default]
+    DataHash,
+    /// The app hash, the application state after the previous block.
+    AppHash,
+    /// The hash of the header itself.
+    HeaderHash,
+}
+
+impl From<LeafContent> for u8 {
+    fn from(value: LeafContent) -> Self {
+        match value {
+            LeafContent::DataHash => 0,
+            LeafContent::AppHash => 1,
+            LeafContent::HeaderHash => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for LeafContent {
+    type Error = ConfigError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(LeafContent::DataHash),
+            1 => Ok(LeafContent::AppHash),
+            2 => Ok(LeafContent::HeaderHash),
+            _ => Err(ConfigError::UnknownLeafContent(value.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for LeafContent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LeafContent::DataHash => f.write_str("data-hash"),
+            LeafContent::AppHash => f.write_str("app-hash"),
+            LeafContent::HeaderHash => f.write_str("header-hash"),
+        }
+    }
+}
+
+impl FromStr for LeafContent {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "data-hash" => Ok(LeafContent::DataHash),
+            "app-hash" => Ok(LeafContent::AppHash),
+            "header-hash" => Ok(LeafContent::HeaderHash),
+            _ => Err(ConfigError::UnknownLeafContent(s.to_string())),
+        }
+    }
+}
+
+/// Configuration passed to the guest ahead of the light blocks, selecting how the inputs are
+/// decoded and what is committed for each header.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ProofConfig {
+    pub proto_version: ProtoVersion,
+    pub leaf_content: LeafContent,
+}
+
+impl ProofConfig {
+    /// Encoded length of the config in the guest input.
+    pub const ENCODED_LEN: usize = 2;
+
+    /// Encodes the config as the prefix of the guest input.
+    pub fn encode(self) -> [u8; Self::ENCODED_LEN] {
+        [self.proto_version.into(), self.leaf_content.into()]
+    }
+
+    /// Decodes the config from the start of the guest input, advancing the cursor past it.
+    pub fn decode(cursor: &mut &[u8]) -> Result<Self, ConfigError> {
+        let Some(([proto_version, leaf_content], rest)) = cursor.split_first_chunk() else {
+            return Err(ConfigError::Missing);
+        };
+        let config = Self {
+            proto_version: (*proto_version).try_into()?,
+            leaf_content: (*leaf_content).try_into()?,
+        };
+        *cursor = rest;
+        Ok(config)
+    }
+}
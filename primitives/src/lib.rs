@@ -15,16 +15,17 @@
 // TODO resolve unexpected test config generated by sol macro and why it doesn't work for r0 target
 #![allow(unexpected_cfgs)]
 
-use abi::IBlobstream::DataRootTuple;
+use abi::IBlobstream::{BinaryMerkleProof, DataRootTuple};
 use alloy_primitives::U256;
 use alloy_sol_types::SolValue;
 use proto::{TrustedLightBlock, UntrustedLightBlock};
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
 use std::collections::BTreeSet;
 use std::iter;
 use std::time::Duration;
 use tendermint::account::Id;
 use tendermint::block::signed_header::SignedHeader;
+use tendermint::chain;
 use tendermint::merkle::simple_hash_from_byte_vectors;
 use tendermint::Hash;
 use tendermint_light_client_verifier::types::{Header, TrustThreshold};
@@ -32,7 +33,16 @@ use tendermint_light_client_verifier::{options::Options, ProdVerifier, Verdict,
 
 pub mod proto;
 
+mod config;
+pub use config::{ConfigError, LeafContent, ProofConfig};
+
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+
 mod abi {
+    // The generated bindings of `initialize` take each of its parameters.
+    #![allow(clippy::too_many_arguments)]
+
     use alloy_sol_types::sol;
 
     #[cfg(not(target_os = "zkvm"))]
@@ -82,24 +92,92 @@ impl MerkleTree {
         self.inner.push(element.abi_encode());
     }
 
+    /// Pushes a header hash to the merkle tree, as the leaf data.
+    pub fn push_hash(&mut self, hash: &MerkleHash) {
+        self.inner.push(hash.to_vec());
+    }
+
     /// Computes and returns the merkle root of tree.
     pub fn root(&mut self) -> MerkleHash {
         simple_hash_from_byte_vectors::<Sha256>(&self.inner)
     }
+
+    /// Computes the inclusion proof of the leaf at `index`, which is the sibling nodes on the path
+    /// from the leaf to the root, as verified by `BinaryMerkleTree.verify` in the contracts.
+    pub fn proof(&self, index: usize) -> BinaryMerkleProof {
+        BinaryMerkleProof {
+            sideNodes: aunts(&self.inner, index)
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            key: U256::from(index),
+            numLeaves: U256::from(self.inner.len()),
+        }
+    }
+}
+
+/// Sibling nodes of the leaf at `index`, ordered from the leaf to the root. Trees are split the
+/// same as [simple_hash_from_byte_vectors], with the left subtree having the largest power of two
+/// number of leaves less than the total.
+fn aunts(leaves: &[Vec<u8>], index: usize) -> Vec<MerkleHash> {
+    if leaves.len() <= 1 {
+        return Vec::new();
+    }
+    let split = leaves.len().next_power_of_two() / 2;
+    let (left, right) = leaves.split_at(split);
+    let (mut aunts, sibling) = if index < split {
+        (aunts(left, index), right)
+    } else {
+        (aunts(right, index - split), left)
+    };
+    aunts.push(simple_hash_from_byte_vectors::<Sha256>(sibling));
+    aunts
+}
+
+/// Hash of the encoded [DataRootTuple] leaf in the merkle tree.
+pub fn data_root_leaf_hash(tuple: &DataRootTuple) -> MerkleHash {
+    // The root of a tree with a single leaf is the leaf hash.
+    simple_hash_from_byte_vectors::<Sha256>(&[tuple.abi_encode()])
+}
+
+/// Data root tuple of a header, which is the leaf committed to for each height. The root of the
+/// tuple is the header field selected by `leaf_content`.
+pub fn data_root_tuple(header: &Header, leaf_content: LeafContent) -> DataRootTuple {
+    let root = match leaf_content {
+        LeafContent::DataHash => data_hash(header),
+        LeafContent::AppHash => app_hash(header),
+        LeafContent::HeaderHash => expect_block_hash(header),
+    };
+    DataRootTuple {
+        height: U256::from(header.height.value()),
+        dataRoot: root.into(),
+    }
+}
+
+/// Merkle roots committed for the headers of a proven range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeMerkleRoots {
+    /// Root of the [DataRootTuple]s, which the contract verifies attestations against. The tuples
+    /// hold the header field selected by the [LeafContent].
+    pub data_root: MerkleHash,
+    /// Root of the header hashes, to prove any field of a header in the range against.
+    pub header_hash_root: MerkleHash,
 }
 
-/// Calculates merkle root of all new blocks proven. This includes the untrusted header and all
-/// interval headers since the trusted block.
+/// Calculates the merkle roots of all new blocks proven. This includes the untrusted header and
+/// all interval headers since the trusted block.
 pub fn build_merkle_root(
     trusted_block: &TrustedLightBlock,
     interval_headers: &[Header],
     untrusted_block: &UntrustedLightBlock,
-) -> MerkleHash {
+    leaf_content: LeafContent,
+) -> RangeMerkleRoots {
     let mut merkle_tree = MerkleTree::default();
+    let mut header_hash_tree = MerkleTree::default();
 
     let trusted_header = trusted_block.signed_header.header();
     let untrusted_header = untrusted_block.signed_header.header();
-    let mut previous = trusted_header;
+    let mut previous_hash = trusted_header.hash();
     for header in interval_headers.iter().chain(iter::once(untrusted_header)) {
         // Check hash links between blocks
         assert_eq!(
@@ -107,21 +185,84 @@ pub fn build_merkle_root(
                 .last_block_id
                 .expect("Header must hash link to previous block")
                 .hash,
-            previous.hash()
+            previous_hash
         );
-        previous = header;
+        // Each header is hashed once, for both the next hash link and the header hash tree.
+        let hash = expect_block_hash(header);
+        previous_hash = Hash::Sha256(hash);
 
-        // Push data root of checked header.
-        merkle_tree.push(&DataRootTuple {
-            height: U256::from(header.height.value()),
-            dataRoot: expect_sha256_data_hash(header).into(),
-        });
+        // Push data root and hash of checked header.
+        let tuple = match leaf_content {
+            // Reuse the header hash rather than hashing the header again.
+            LeafContent::HeaderHash => DataRootTuple {
+                height: U256::from(header.height.value()),
+                dataRoot: hash.into(),
+            },
+            _ => data_root_tuple(header, leaf_content),
+        };
+        merkle_tree.push(&tuple);
+        header_hash_tree.push_hash(&hash);
     }
 
-    merkle_tree.root()
+    RangeMerkleRoots {
+        data_root: merkle_tree.root(),
+        header_hash_root: header_hash_tree.root(),
+    }
+}
+
+/// Builds the merkle tree of the data roots of `headers` the same as [build_merkle_root], and
+/// returns the merkle root with the data root tuple and inclusion proof for `height`. The headers
+/// must be all headers of a proven range, excluding the trusted block, in ascending order.
+///
+/// Returns [None] if `height` is not in `headers`.
+pub fn data_root_inclusion_proof(
+    headers: &[Header],
+    height: u64,
+    leaf_content: LeafContent,
+) -> Option<(MerkleHash, DataRootTuple, BinaryMerkleProof)> {
+    let index = headers
+        .iter()
+        .position(|header| header.height.value() == height)?;
+
+    let mut merkle_tree = MerkleTree::default();
+    for header in headers {
+        merkle_tree.push(&data_root_tuple(header, leaf_content));
+    }
+
+    Some((
+        merkle_tree.root(),
+        data_root_tuple(&headers[index], leaf_content),
+        merkle_tree.proof(index),
+    ))
 }
 
-/// Verify light client transition from trusted block to untrusted.
+/// Builds the merkle tree of the header hashes of `headers` the same as [build_merkle_root], and
+/// returns the merkle root with the header hash and inclusion proof for `height`. The headers must
+/// be all headers of a proven range, excluding the trusted block, in ascending order.
+///
+/// Returns [None] if `height` is not in `headers`.
+pub fn header_hash_inclusion_proof(
+    headers: &[Header],
+    height: u64,
+) -> Option<(MerkleHash, MerkleHash, BinaryMerkleProof)> {
+    let index = headers
+        .iter()
+        .position(|header| header.height.value() == height)?;
+
+    let mut merkle_tree = MerkleTree::default();
+    for header in headers {
+        merkle_tree.push_hash(&expect_block_hash(header));
+    }
+
+    Some((
+        merkle_tree.root(),
+        expect_block_hash(&headers[index]),
+        merkle_tree.proof(index),
+    ))
+}
+
+/// Verify light client transition from trusted block to untrusted. Validators can have ed25519 or
+/// secp256k1 keys, including a mix of both in the same validator set.
 pub fn light_client_verify(
     trusted_block: &TrustedLightBlock,
     untrusted_block: &UntrustedLightBlock,
@@ -192,10 +333,183 @@ pub fn expect_block_hash(block: &Header) -> [u8; 32] {
     hash
 }
 
-/// Convenience function to pull the header's data hash, assuming a Sha256 hash.
-fn expect_sha256_data_hash(header: &Header) -> [u8; 32] {
-    let Some(Hash::Sha256(hash)) = header.data_hash else {
-        unreachable!("Header data root should always be a non empty sha256");
+/// Hash of the Tendermint chain ID committed to by the guest, which the contract checks to only
+/// accept proofs of the chain it tracks.
+pub fn chain_id_hash(chain_id: &chain::Id) -> [u8; 32] {
+    Sha256::digest(chain_id.as_str().as_bytes()).into()
+}
+
+/// Unix time of the header in seconds, as committed by the guest.
+pub fn header_time(header: &Header) -> u64 {
+    u64::try_from(header.time.unix_timestamp()).expect("header time must be after the unix epoch")
+}
+
+/// The header's data hash, or zero for headers without one, such as the empty blocks of chains
+/// that omit the hash when there are no transactions.
+fn data_hash(header: &Header) -> [u8; 32] {
+    match header.data_hash {
+        Some(Hash::Sha256(hash)) => hash,
+        Some(Hash::None) | None => [0; 32],
+    }
+}
+
+/// The header's app hash, which has no fixed length. Hashes of 32 bytes are used as is, empty
+/// hashes as zero, and hashes of any other length are hashed with SHA-256.
+fn app_hash(header: &Header) -> [u8; 32] {
+    let app_hash = header.app_hash.as_bytes();
+    match app_hash.len() {
+        0 => [0; 32],
+        32 => app_hash.try_into().expect("length checked"),
+        _ => Sha256::digest(app_hash).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{b256, B256};
+    use tendermint::{validator, PublicKey};
+    use tendermint_light_client_verifier::errors::VerificationErrorDetail;
+
+    use super::{
+        test_utils::{block_id, commit, header, validator_set, SigningKey, Validator},
+        *,
     };
-    hash
+
+    /// Header hashes of a range of five headers, which is not a power of two.
+    fn header_hash_tree() -> MerkleTree {
+        let mut merkle_tree = MerkleTree::default();
+        for i in 1..=5 {
+            merkle_tree.push_hash(&B256::from(U256::from(i)).0);
+        }
+        merkle_tree
+    }
+
+    /// The same values are verified on-chain by `testVerifyHeaderHash` in the contract tests.
+    #[test]
+    fn header_hash_proof_vectors() {
+        let mut merkle_tree = header_hash_tree();
+        assert_eq!(
+            B256::from(merkle_tree.root()),
+            b256!("c48c0df7d9b37592c69ba5ca2afc8ada511550e607e6dfe7fdef6b85d89f5269")
+        );
+
+        let proof = merkle_tree.proof(2);
+        assert_eq!(proof.key, U256::from(2));
+        assert_eq!(proof.numLeaves, U256::from(5));
+        assert_eq!(
+            proof.sideNodes,
+            [
+                b256!("82f02cf2ac0074619e6d747c35e08b29431a16943ddf81cfd9065c004ee6364a"),
+                b256!("0971c8a1ce81287ccbc95aa4f171a5f807fb13ea2118f56b99769459a64906ad"),
+                b256!("086fb60bd968fe68ecec6a8d826ea5aa7d3d8020e644d7c5d0e07ded456ca3e8"),
+            ]
+        );
+
+        let proof = merkle_tree.proof(4);
+        assert_eq!(
+            proof.sideNodes,
+            [b256!(
+                "45f385494f9f6116ec5530e7e9e24e2fdf6388c47d72e29657ce7b860c1484c3"
+            )]
+        );
+    }
+
+    /// Four validators of equal power, alternating between ed25519 and secp256k1 keys.
+    fn mixed_key_validators() -> Vec<Validator> {
+        (1..=4u8)
+            .map(|i| {
+                let seed = [i; 32];
+                let key = if i % 2 == 0 {
+                    SigningKey::Secp256k1(
+                        k256::ecdsa::SigningKey::from_bytes(&seed.into()).unwrap(),
+                    )
+                } else {
+                    SigningKey::Ed25519(ed25519_consensus::SigningKey::from(seed))
+                };
+                Validator::new(key, 10)
+            })
+            .collect()
+    }
+
+    /// Adjacent light blocks signed by a validator set with mixed key types.
+    fn mixed_key_light_blocks(
+        absent: Option<Id>,
+        forged: Option<Id>,
+    ) -> (TrustedLightBlock, UntrustedLightBlock) {
+        let keys = mixed_key_validators();
+        let validators = validator_set(&keys);
+
+        let trusted_header = header(
+            10,
+            block_id(Hash::Sha256([7; 32])),
+            &validators,
+            &validators,
+        );
+        let untrusted_header = header(
+            11,
+            block_id(trusted_header.hash()),
+            &validators,
+            &validators,
+        );
+        let trusted_commit = commit(&trusted_header, &validators, &keys, None, None);
+        let untrusted_commit = commit(&untrusted_header, &validators, &keys, absent, forged);
+
+        (
+            TrustedLightBlock {
+                signed_header: SignedHeader::new(trusted_header, trusted_commit).unwrap(),
+                next_validators: validators.clone(),
+            },
+            UntrustedLightBlock {
+                signed_header: SignedHeader::new(untrusted_header, untrusted_commit).unwrap(),
+                validators,
+            },
+        )
+    }
+
+    fn is_secp256k1(info: &validator::Info) -> bool {
+        matches!(info.pub_key, PublicKey::Secp256k1(_))
+    }
+
+    #[test]
+    fn mixed_key_light_client_verify() {
+        let (trusted_block, _) = mixed_key_light_blocks(None, None);
+        let validators = trusted_block.next_validators.validators();
+        assert!(validators.iter().any(is_secp256k1));
+        assert!(!validators.iter().all(is_secp256k1));
+
+        // One validator not signing the untrusted block leaves three quarters of the voting power.
+        let absent_index = 1;
+        let absent = validators[absent_index].address;
+        let (trusted_block, untrusted_block) = mixed_key_light_blocks(Some(absent), None);
+        assert!(matches!(
+            light_client_verify(&trusted_block, &untrusted_block),
+            Verdict::Success
+        ));
+
+        let mut expected_bitmap = U256::ZERO;
+        for index in (0..validators.len()).filter(|index| *index != absent_index) {
+            expected_bitmap.set_bit(index, true);
+        }
+        assert_eq!(
+            generate_bitmap(&trusted_block, &untrusted_block),
+            expected_bitmap
+        );
+    }
+
+    #[test]
+    fn forged_secp256k1_signature_fails() {
+        let (trusted_block, _) = mixed_key_light_blocks(None, None);
+        let forged = trusted_block
+            .next_validators
+            .validators()
+            .iter()
+            .find(|info| is_secp256k1(info))
+            .unwrap()
+            .address;
+        let (trusted_block, untrusted_block) = mixed_key_light_blocks(None, Some(forged));
+        assert!(matches!(
+            light_client_verify(&trusted_block, &untrusted_block),
+            Verdict::Invalid(VerificationErrorDetail::InvalidSignature(_))
+        ));
+    }
 }
@@ -16,47 +16,229 @@
 //!
 //! Types must be manually converted from the ones returned from the RPC API to be compatible,
 //! but also to remove unnecessary data from being encoded into the zkvm.
+//!
+//! The light blocks and headers can be encoded with the protobuf definitions of any supported
+//! CometBFT version, selected with [ProtoVersion].
+
+use std::fmt;
+use std::str::FromStr;
 
 use tendermint_light_client_verifier::types::{
-    SignedHeader, TrustedBlockState, UntrustedBlockState, ValidatorSet,
+    Header, SignedHeader, TrustedBlockState, UntrustedBlockState, ValidatorSet,
 };
-use tendermint_proto::{types::LightBlock as ProtoLightBlock, Protobuf};
+use tendermint_proto::{v0_34, v0_37, v0_38, Protobuf};
 
-/// Wrapper type around [SignedHeader] and its respective next [ValidatorSet] necessary to validate
-/// a light client transition against.
-#[derive(Clone, Debug)]
-pub struct TrustedLightBlock {
-    pub signed_header: SignedHeader,
-    pub next_validators: ValidatorSet,
+use crate::ConfigError;
+
+/// Version of the CometBFT protobuf definitions that the guest input is encoded with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ProtoVersion {
+    V0_34,
+    #[default]
+    V0_37,
+    V0_38,
 }
 
-impl TryFrom<ProtoLightBlock> for TrustedLightBlock {
-    type Error = tendermint::Error;
-
-    fn try_from(value: ProtoLightBlock) -> Result<Self, Self::Error> {
-        Ok(Self {
-            signed_header: value
-                .signed_header
-                .ok_or(tendermint::Error::missing_header())?
-                .try_into()?,
-            next_validators: value
-                .validator_set
-                .ok_or(tendermint::Error::missing_validator())?
-                .try_into()?,
-        })
+impl ProtoVersion {
+    /// Encodes a light block, length delimited.
+    pub fn encode_light_block<T: VersionedLightBlock>(
+        self,
+        block: T,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), tendermint_proto::Error> {
+        block.encode_versioned(self, buf)
+    }
+
+    /// Decodes a length delimited light block, advancing the cursor past it.
+    pub fn decode_light_block<T: VersionedLightBlock>(
+        self,
+        cursor: &mut &[u8],
+    ) -> Result<T, tendermint_proto::Error> {
+        T::decode_versioned(self, cursor)
+    }
+
+    /// Encodes a header, length delimited.
+    pub fn encode_header(
+        self,
+        header: Header,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), tendermint_proto::Error> {
+        match self {
+            ProtoVersion::V0_34 => {
+                Protobuf::<v0_34::types::Header>::encode_length_delimited(header, buf)
+            }
+            ProtoVersion::V0_37 => {
+                Protobuf::<v0_37::types::Header>::encode_length_delimited(header, buf)
+            }
+            ProtoVersion::V0_38 => {
+                Protobuf::<v0_38::types::Header>::encode_length_delimited(header, buf)
+            }
+        }
+    }
+
+    /// Decodes a length delimited header, advancing the cursor past it.
+    pub fn decode_header(self, cursor: &mut &[u8]) -> Result<Header, tendermint_proto::Error> {
+        match self {
+            ProtoVersion::V0_34 => {
+                Protobuf::<v0_34::types::Header>::decode_length_delimited(cursor)
+            }
+            ProtoVersion::V0_37 => {
+                Protobuf::<v0_37::types::Header>::decode_length_delimited(cursor)
+            }
+            ProtoVersion::V0_38 => {
+                Protobuf::<v0_38::types::Header>::decode_length_delimited(cursor)
+            }
+        }
+    }
+}
+
+impl From<ProtoVersion> for u8 {
+    fn from(value: ProtoVersion) -> Self {
+        match value {
+            ProtoVersion::V0_34 => 34,
+            ProtoVersion::V0_37 => 37,
+            ProtoVersion::V0_38 => 38,
+        }
+    }
+}
+
+impl TryFrom<u8> for ProtoVersion {
+    type Error = ConfigError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            34 => Ok(ProtoVersion::V0_34),
+            37 => Ok(ProtoVersion::V0_37),
+            38 => Ok(ProtoVersion::V0_38),
+            _ => Err(ConfigError::UnknownProtoVersion(value.to_string())),
+        }
     }
 }
 
-impl From<TrustedLightBlock> for ProtoLightBlock {
-    fn from(value: TrustedLightBlock) -> Self {
-        Self {
-            signed_header: Some(value.signed_header.into()),
-            validator_set: Some(value.next_validators.into()),
+impl fmt::Display for ProtoVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtoVersion::V0_34 => f.write_str("0.34"),
+            ProtoVersion::V0_37 => f.write_str("0.37"),
+            ProtoVersion::V0_38 => f.write_str("0.38"),
         }
     }
 }
 
-impl Protobuf<ProtoLightBlock> for TrustedLightBlock {}
+impl FromStr for ProtoVersion {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim_start_matches('v') {
+            "0.34" => Ok(ProtoVersion::V0_34),
+            "0.37" => Ok(ProtoVersion::V0_37),
+            "0.38" => Ok(ProtoVersion::V0_38),
+            _ => Err(ConfigError::UnknownProtoVersion(s.to_string())),
+        }
+    }
+}
+
+/// Light block wrapper that can be encoded with the protobuf definitions of each [ProtoVersion].
+pub trait VersionedLightBlock: Sized {
+    /// Encodes the light block with the given version, length delimited.
+    fn encode_versioned(
+        self,
+        version: ProtoVersion,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), tendermint_proto::Error>;
+
+    /// Decodes a length delimited light block with the given version, advancing the cursor.
+    fn decode_versioned(
+        version: ProtoVersion,
+        cursor: &mut &[u8],
+    ) -> Result<Self, tendermint_proto::Error>;
+}
+
+/// Implements the protobuf conversions of a light block wrapper for each supported version, with
+/// the name of its validator set field.
+macro_rules! impl_light_block_protobuf {
+    ($ty:ident, $validators:ident) => {
+        impl_light_block_protobuf!($ty, $validators, v0_34, v0_37, v0_38);
+
+        impl VersionedLightBlock for $ty {
+            fn encode_versioned(
+                self,
+                version: ProtoVersion,
+                buf: &mut Vec<u8>,
+            ) -> Result<(), tendermint_proto::Error> {
+                match version {
+                    ProtoVersion::V0_34 => {
+                        Protobuf::<v0_34::types::LightBlock>::encode_length_delimited(self, buf)
+                    }
+                    ProtoVersion::V0_37 => {
+                        Protobuf::<v0_37::types::LightBlock>::encode_length_delimited(self, buf)
+                    }
+                    ProtoVersion::V0_38 => {
+                        Protobuf::<v0_38::types::LightBlock>::encode_length_delimited(self, buf)
+                    }
+                }
+            }
+
+            fn decode_versioned(
+                version: ProtoVersion,
+                cursor: &mut &[u8],
+            ) -> Result<Self, tendermint_proto::Error> {
+                match version {
+                    ProtoVersion::V0_34 => {
+                        Protobuf::<v0_34::types::LightBlock>::decode_length_delimited(cursor)
+                    }
+                    ProtoVersion::V0_37 => {
+                        Protobuf::<v0_37::types::LightBlock>::decode_length_delimited(cursor)
+                    }
+                    ProtoVersion::V0_38 => {
+                        Protobuf::<v0_38::types::LightBlock>::decode_length_delimited(cursor)
+                    }
+                }
+            }
+        }
+    };
+    ($ty:ident, $validators:ident, $($version:ident),+) => {
+        $(
+            impl TryFrom<$version::types::LightBlock> for $ty {
+                type Error = tendermint::Error;
+
+                fn try_from(value: $version::types::LightBlock) -> Result<Self, Self::Error> {
+                    Ok(Self {
+                        signed_header: value
+                            .signed_header
+                            .ok_or(tendermint::Error::missing_header())?
+                            .try_into()?,
+                        $validators: value
+                            .validator_set
+                            .ok_or(tendermint::Error::missing_validator())?
+                            .try_into()?,
+                    })
+                }
+            }
+
+            impl From<$ty> for $version::types::LightBlock {
+                fn from(value: $ty) -> Self {
+                    Self {
+                        signed_header: Some(value.signed_header.into()),
+                        validator_set: Some(value.$validators.into()),
+                    }
+                }
+            }
+
+            impl Protobuf<$version::types::LightBlock> for $ty {}
+        )+
+    };
+}
+
+/// Wrapper type around [SignedHeader] and its respective next [ValidatorSet] necessary to validate
+/// a light client transition against.
+#[derive(Clone, Debug)]
+pub struct TrustedLightBlock {
+    pub signed_header: SignedHeader,
+    pub next_validators: ValidatorSet,
+}
+
+impl_light_block_protobuf!(TrustedLightBlock, next_validators);
 
 impl TrustedLightBlock {
     /// Convert the trusted light block into type used in
@@ -82,33 +264,7 @@ pub struct UntrustedLightBlock {
     pub validators: ValidatorSet,
 }
 
-impl TryFrom<ProtoLightBlock> for UntrustedLightBlock {
-    type Error = tendermint::Error;
-
-    fn try_from(value: ProtoLightBlock) -> Result<Self, Self::Error> {
-        Ok(Self {
-            signed_header: value
-                .signed_header
-                .ok_or(tendermint::Error::missing_header())?
-                .try_into()?,
-            validators: value
-                .validator_set
-                .ok_or(tendermint::Error::missing_validator())?
-                .try_into()?,
-        })
-    }
-}
-
-impl From<UntrustedLightBlock> for ProtoLightBlock {
-    fn from(value: UntrustedLightBlock) -> Self {
-        Self {
-            signed_header: Some(value.signed_header.into()),
-            validator_set: Some(value.validators.into()),
-        }
-    }
-}
-
-impl Protobuf<ProtoLightBlock> for UntrustedLightBlock {}
+impl_light_block_protobuf!(UntrustedLightBlock, validators);
 
 impl UntrustedLightBlock {
     /// Convert the untrusted light block into type used in
@@ -17,6 +17,7 @@
 use tendermint_light_client_verifier::types::Header;
 
 use crate::proto::{TrustedLightBlock, UntrustedLightBlock};
+use crate::ProofConfig;
 
 /// Inputs for light client block proving for Blobstream. Serialized as tuple for more compact form.
 #[derive(Debug)]
@@ -24,6 +25,8 @@ pub struct LightBlockProveData {
     pub trusted_block: TrustedLightBlock,
     pub interval_headers: Vec<Header>,
     pub untrusted_block: UntrustedLightBlock,
+    /// Configuration the inputs are encoded and proven with.
+    pub config: ProofConfig,
 }
 
 impl LightBlockProveData {
@@ -0,0 +1,193 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fixtures for tests and benchmarks: validators with ed25519 and secp256k1 consensus keys, and the
+//! headers and commits they sign.
+
+use std::{collections::HashMap, time::Duration};
+
+use k256::ecdsa::signature::Signer;
+use tendermint::{
+    account,
+    block::{
+        self, header::Version, parts::Header as PartSetHeader, Commit, CommitSig, Header, Height,
+        Round,
+    },
+    hash::AppHash,
+    validator,
+    vote::{self, ValidatorIndex, Vote},
+    Hash, PublicKey, Signature, Time,
+};
+
+/// Chain ID of the generated headers.
+pub const CHAIN_ID: &str = "blobstream0-test";
+
+/// Unix timestamp of the first block of the chain.
+pub const GENESIS_TIME: u64 = 1_700_000_000;
+
+/// Seconds between blocks.
+pub const BLOCK_TIME: u64 = 6;
+
+/// Consensus key of a validator.
+pub enum SigningKey {
+    Ed25519(ed25519_consensus::SigningKey),
+    Secp256k1(k256::ecdsa::SigningKey),
+}
+
+impl SigningKey {
+    pub fn public_key(&self) -> PublicKey {
+        match self {
+            Self::Ed25519(key) => PublicKey::from_raw_ed25519(key.verification_key().as_bytes())
+                .expect("invalid ed25519 public key"),
+            Self::Secp256k1(key) => {
+                PublicKey::from_raw_secp256k1(key.verifying_key().to_encoded_point(true).as_bytes())
+                    .expect("invalid secp256k1 public key")
+            }
+        }
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        let bytes = match self {
+            Self::Ed25519(key) => key.sign(message).to_bytes().to_vec(),
+            Self::Secp256k1(key) => {
+                // Signed over the SHA-256 digest of the message, with a normalized low S value
+                // as verified by Tendermint.
+                let signature: k256::ecdsa::Signature = key.sign(message);
+                signature.to_bytes().to_vec()
+            }
+        };
+        Signature::new(bytes)
+            .expect("invalid signature")
+            .expect("empty signature")
+    }
+}
+
+/// Validator with the key it signs votes with.
+pub struct Validator {
+    pub key: SigningKey,
+    pub info: validator::Info,
+}
+
+impl Validator {
+    pub fn new(key: SigningKey, power: u32) -> Self {
+        let info = validator::Info::new(key.public_key(), vote::Power::from(power));
+        Self { key, info }
+    }
+}
+
+pub fn validator_set<'a>(validators: impl IntoIterator<Item = &'a Validator>) -> validator::Set {
+    validator::Set::new(
+        validators
+            .into_iter()
+            .map(|validator| validator.info.clone())
+            .collect(),
+        None,
+    )
+}
+
+pub fn block_id(hash: Hash) -> block::Id {
+    block::Id {
+        hash,
+        part_set_header: PartSetHeader::new(1, hash).expect("invalid part set header"),
+    }
+}
+
+pub fn block_time(height: u64) -> Time {
+    let timestamp = GENESIS_TIME + height * BLOCK_TIME;
+    Time::from_unix_timestamp(timestamp as i64, 0).expect("invalid block time")
+}
+
+/// Header at `height` following `last_block_id`, with fixed hashes for the fields the light client
+/// does not check.
+pub fn header(
+    height: u64,
+    last_block_id: block::Id,
+    validators: &validator::Set,
+    next_validators: &validator::Set,
+) -> Header {
+    Header {
+        version: Version { block: 11, app: 1 },
+        chain_id: CHAIN_ID.parse().expect("invalid chain ID"),
+        height: Height::try_from(height).expect("invalid height"),
+        time: block_time(height),
+        last_block_id: Some(last_block_id),
+        last_commit_hash: Some(Hash::Sha256([1; 32])),
+        data_hash: Some(Hash::Sha256([2; 32])),
+        validators_hash: validators.hash(),
+        next_validators_hash: next_validators.hash(),
+        consensus_hash: Hash::Sha256([3; 32]),
+        app_hash: AppHash::default(),
+        last_results_hash: Some(Hash::Sha256([4; 32])),
+        evidence_hash: Some(Hash::Sha256([5; 32])),
+        proposer_address: validators.validators()[0].address,
+    }
+}
+
+/// Commit of the header with precommit votes signed by every validator of the set except
+/// `absent`, with keys from `signers`. The vote of `forged` is for another block.
+pub fn commit<'a>(
+    header: &Header,
+    validators: &validator::Set,
+    signers: impl IntoIterator<Item = &'a Validator>,
+    absent: Option<account::Id>,
+    forged: Option<account::Id>,
+) -> Commit {
+    let keys: HashMap<account::Id, &SigningKey> = signers
+        .into_iter()
+        .map(|validator| (validator.info.address, &validator.key))
+        .collect();
+    let block_id = block_id(header.hash());
+    let timestamp = (header.time + Duration::from_secs(1)).expect("invalid commit time");
+    let signatures = validators
+        .validators()
+        .iter()
+        .enumerate()
+        .map(|(index, validator)| {
+            if Some(validator.address) == absent {
+                return CommitSig::BlockIdFlagAbsent;
+            }
+            let vote_block_id = if Some(validator.address) == forged {
+                self::block_id(Hash::Sha256([6; 32]))
+            } else {
+                block_id
+            };
+            let vote = Vote {
+                vote_type: vote::Type::Precommit,
+                height: header.height,
+                round: Round::default(),
+                block_id: Some(vote_block_id),
+                timestamp: Some(timestamp),
+                validator_address: validator.address,
+                validator_index: ValidatorIndex::try_from(index as u32)
+                    .expect("invalid validator index"),
+                signature: None,
+                extension: Vec::new(),
+                extension_signature: None,
+            };
+            let sign_bytes = vote.into_signable_vec(header.chain_id.clone());
+            CommitSig::BlockIdFlagCommit {
+                validator_address: validator.address,
+                timestamp,
+                signature: Some(keys[&validator.address].sign(&sign_bytes)),
+            }
+        })
+        .collect();
+
+    Commit {
+        height: header.height,
+        round: Round::default(),
+        block_id,
+        signatures,
+    }
+}